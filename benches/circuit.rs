@@ -0,0 +1,142 @@
+//! Times circuit emulation on the puzzle input, with and without compiling the
+//! circuit each time, and on a chain of wires far too deep to recurse through.
+//! A recursive evaluator like the one compiled plans replaced is timed on the
+//! puzzle input alongside, for comparison.
+//!
+//! Run with `cargo bench --bench circuit`.
+
+use aoc::circuit::{wire_name, Circuit, Gate, Instruction};
+use aoc::parsing::parse_lines;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const INPUT: &str = include_str!("../src/bin/circuit_input");
+
+fn main() {
+    let circuit = Circuit {
+        instructions: parse_lines(INPUT, str::parse)
+            .collect::<Result<_, _>>()
+            .unwrap(),
+    };
+
+    let drivers = drivers(INPUT);
+    assert_eq!(recursive_emulate(&drivers), circuit.emulate().unwrap());
+
+    bench("recursively emulate circuit_input", || {
+        recursive_emulate(&drivers)
+    });
+
+    bench("compile and emulate circuit_input", || {
+        circuit.emulate().unwrap()
+    });
+
+    let plan = circuit.compile().unwrap();
+
+    bench("emulate compiled circuit_input", || plan.emulate());
+
+    bench("evaluate compiled circuit_input", || plan.signals());
+
+    let signals = plan.signals();
+
+    bench("override wire b in circuit_input", || {
+        signals.with_overrides(&[("b", 1)]).unwrap()
+    });
+
+    let chain = Circuit {
+        instructions: (0..100_000).map(chain_link).collect(),
+    };
+
+    bench("compile and emulate a chain of 100,000 wires", || {
+        chain.emulate().unwrap()
+    });
+}
+
+enum Driver<'a> {
+    Single(&'a str),
+    Not(&'a str),
+    Gate(&'a str, Gate, &'a str),
+}
+
+fn drivers(input: &str) -> HashMap<&str, Driver<'_>> {
+    input
+        .lines()
+        .map(|line| {
+            let (signal, wire) = line.split_once(" -> ").unwrap();
+
+            let driver = match signal.split(' ').collect::<Vec<_>>()[..] {
+                [single] => Driver::Single(single),
+                ["NOT", operand] => Driver::Not(operand),
+                [lhs, gate, rhs] => Driver::Gate(lhs, gate.parse().unwrap(), rhs),
+                _ => panic!("invalid signal {}", signal),
+            };
+
+            (wire, driver)
+        })
+        .collect()
+}
+
+/// How circuits were emulated before they were compiled.
+fn recursive_emulate<'a>(drivers: &HashMap<&'a str, Driver<'a>>) -> HashMap<&'a str, u16> {
+    let mut cache = HashMap::with_capacity(drivers.len());
+
+    drivers
+        .keys()
+        .map(|&wire| (wire, recursive_signal(wire, drivers, &mut cache)))
+        .collect()
+}
+
+fn recursive_signal<'a>(
+    operand: &'a str,
+    drivers: &HashMap<&'a str, Driver<'a>>,
+    cache: &mut HashMap<&'a str, u16>,
+) -> u16 {
+    if let Ok(literal) = operand.parse() {
+        return literal;
+    }
+
+    if let Some(&signal) = cache.get(operand) {
+        return signal;
+    }
+
+    let signal = match drivers[operand] {
+        Driver::Single(single) => recursive_signal(single, drivers, cache),
+        Driver::Not(operand) => !recursive_signal(operand, drivers, cache),
+        Driver::Gate(lhs, gate, rhs) => {
+            let lhs = recursive_signal(lhs, drivers, cache);
+            let rhs = recursive_signal(rhs, drivers, cache);
+
+            gate.apply_to(lhs.into(), rhs.into(), 16) as u16
+        }
+    };
+
+    cache.insert(operand, signal);
+    signal
+}
+
+fn chain_link(idx: usize) -> Instruction {
+    let instruction = if idx == 0 {
+        format!("1 -> {}", wire_name(0))
+    } else {
+        format!("NOT {} -> {}", wire_name(idx - 1), wire_name(idx))
+    };
+
+    instruction.parse().unwrap()
+}
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let start = Instant::now();
+    let mut iterations = 0;
+
+    while start.elapsed() < Duration::from_secs(1) {
+        black_box(f());
+        iterations += 1;
+    }
+
+    println!(
+        "{:<45} {:>12.1?} per iteration ({} iterations)",
+        name,
+        start.elapsed() / iterations,
+        iterations,
+    );
+}
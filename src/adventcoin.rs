@@ -1,12 +1,12 @@
 use md5::{Digest, Md5};
 
-pub struct Miner {
-    key: &'static str,
+pub struct Miner<'a> {
+    key: &'a str,
     current_guess: u32,
 }
 
-impl Miner {
-    pub fn new(key: &'static str) -> Self {
+impl<'a> Miner<'a> {
+    pub fn new(key: &'a str) -> Self {
         Self {
             key,
             current_guess: 0,
@@ -1,13 +1,20 @@
 use aoc::boarding::{find_seat, BoardingPass};
+use aoc::input::exit_with_errors;
+use aoc::parsing::parse_all_lines;
 
 const INPUT: &str = include_str!("boarding_input");
 
 fn main() {
-    let seat_locations: Vec<_> = INPUT
-        .lines()
-        .map(BoardingPass::from)
-        .map(|pass| pass.location())
-        .collect();
+    let seat_locations: Vec<_> = parse_all_lines(INPUT, |line| {
+        line.parse().map(|pass: BoardingPass| pass.location())
+    })
+    .unwrap_or_else(|errors| {
+        exit_with_errors(
+            errors
+                .iter()
+                .map(|error| error.render("boarding_input", INPUT)),
+        )
+    });
 
     let highest_id = seat_locations.iter().map(|location| location.id()).max();
 
@@ -17,6 +24,9 @@ fn main() {
         println!("No seat ID was found");
     }
 
-    let seat_location = find_seat(&seat_locations);
-    println!("Your seat location’s ID is {}", seat_location.id());
+    if let Some(seat_location) = find_seat(&seat_locations) {
+        println!("Your seat location’s ID is {}", seat_location.id());
+    } else {
+        println!("Your seat could not be found");
+    }
 }
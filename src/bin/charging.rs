@@ -1,11 +1,19 @@
 use aoc::charging::{Adapters, Difference};
+use std::process;
 use std::str::FromStr;
 
 const INPUT: &str = include_str!("charging_input");
 
 fn main() {
-    let mut adapters = Adapters::from_str(INPUT).unwrap();
-    let differences = adapters.differences_to_outlet();
+    let mut adapters = Adapters::from_str(INPUT).unwrap_or_else(|error| {
+        eprintln!("{}", error.render("charging_input", INPUT));
+        process::exit(1);
+    });
+
+    let differences = adapters.differences_to_outlet().unwrap_or_else(|| {
+        eprintln!("The adapters do not all differ by one or three jolts");
+        process::exit(1);
+    });
 
     let num_one_jolt_differences = differences
         .iter()
@@ -1,25 +1,147 @@
-use aoc::circuit::Circuit;
+use aoc::circuit::{Circuit, CircuitError, Settling};
+use aoc::input::exit_with_errors;
+use aoc::parsing::parse_all_lines;
+use std::io::{self, BufWriter};
+use std::{env, process};
 
 const INPUT: &str = include_str!("circuit_input");
 
-fn main() {
-    let instructions = INPUT.lines().map(|line| line.parse().unwrap());
+const USAGE: &str = "usage: circuit [--dot [WIRE] | --vcd | --verilog | --analyze [OUTPUT...]]";
+
+/// The most steps `--vcd` simulates before giving up on the circuit settling.
+const MAX_STEPS: usize = 10_000;
+
+enum Mode {
+    Answers,
+    Dot { cone: Option<String> },
+    Vcd,
+    Verilog,
+    Analyze { outputs: Vec<String> },
+}
 
-    let circuit = Circuit {
-        instructions: instructions.collect(),
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let mode = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => Mode::Answers,
+        ["--dot"] => Mode::Dot { cone: None },
+        ["--dot", cone] => Mode::Dot {
+            cone: Some(cone.to_string()),
+        },
+        ["--vcd"] => Mode::Vcd,
+        ["--verilog"] => Mode::Verilog,
+        ["--analyze"] => Mode::Analyze {
+            outputs: vec!["a".to_string()],
+        },
+        ["--analyze", ..] => Mode::Analyze {
+            outputs: args[1..].to_vec(),
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
     };
 
-    let wire_signals = circuit.emulate();
+    let instructions = parse_all_lines(INPUT, str::parse).unwrap_or_else(|errors| {
+        exit_with_errors(
+            errors
+                .iter()
+                .map(|error| error.render("circuit_input", INPUT)),
+        )
+    });
 
-    println!(
-        "The signal being provided to wire a is {}",
-        wire_signals.get("a").unwrap(),
-    );
+    let circuit = Circuit { instructions };
+
+    match mode {
+        Mode::Answers => print_answers(&circuit),
+        Mode::Dot { cone } => print_dot(&circuit, cone.as_deref()),
+        Mode::Vcd => write_vcd(&circuit),
+        Mode::Verilog => print_verilog(&circuit),
+        Mode::Analyze { outputs } => print_analysis(&circuit, &outputs),
+    }
+}
+
+fn print_answers(circuit: &Circuit) {
+    let plan = circuit
+        .compile()
+        .unwrap_or_else(|errors| exit_with(&errors));
+    let wire_signals = plan.signals();
+
+    let a_signal = wire_signals.get("a").unwrap_or_else(|| {
+        eprintln!("error: the circuit has no wire a");
+        process::exit(1);
+    });
+
+    println!("The signal being provided to wire a is {}", a_signal);
 
-    let overriden_a_signal = circuit.override_and_clear(wire_signals, "a", "b");
+    let overriden_a_signal = wire_signals
+        .with_overrides(&[("b", a_signal)])
+        .unwrap_or_else(|error| exit_with(&[error]))
+        .get("a")
+        .unwrap();
 
     println!(
         "The signal being provided to wire a after overriding is {}",
         overriden_a_signal,
     );
 }
+
+fn print_dot(circuit: &Circuit, cone: Option<&str>) {
+    let values = circuit
+        .emulate()
+        .unwrap_or_else(|errors| exit_with(&errors));
+    let dot = circuit.to_dot().with_values(&values);
+
+    let dot = match cone {
+        Some(wire) => dot
+            .cone_of(wire)
+            .unwrap_or_else(|error| exit_with(&[error])),
+        None => dot,
+    };
+
+    print!("{}", dot);
+}
+
+fn write_vcd(circuit: &Circuit) {
+    let simulation = circuit
+        .simulate(MAX_STEPS)
+        .unwrap_or_else(|errors| exit_with(&errors));
+
+    let stdout = io::stdout();
+
+    if let Err(error) = simulation.write_vcd(BufWriter::new(stdout.lock())) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+
+    if let Settling::Stable { .. } = simulation.settling() {
+        eprintln!("{}", simulation.settling());
+    } else {
+        eprintln!("error: {}", simulation.settling());
+        process::exit(1);
+    }
+}
+
+fn print_verilog(circuit: &Circuit) {
+    let verilog = circuit
+        .to_verilog()
+        .unwrap_or_else(|errors| exit_with(&errors));
+
+    print!("{}", verilog);
+}
+
+fn print_analysis(circuit: &Circuit, outputs: &[String]) {
+    let outputs: Vec<_> = outputs.iter().map(String::as_str).collect();
+    let analysis = circuit
+        .analyze(&outputs)
+        .unwrap_or_else(|errors| exit_with(&errors));
+
+    print!("{}", analysis);
+}
+
+fn exit_with(errors: &[CircuitError]) -> ! {
+    for error in errors {
+        eprintln!("error: {}", error);
+    }
+
+    process::exit(1);
+}
@@ -1,20 +1,39 @@
 use aoc::console::Vm;
+use aoc::input::{exit_with_errors, Source};
+use std::process;
 
 const INPUT: &str = include_str!("console_input");
 
 fn main() {
-    let instructions = INPUT.lines().map(|line| line.parse().unwrap());
-    let mut vm = Vm::new(instructions);
+    let Source { name, reader } =
+        Source::from_args("console_input", INPUT).unwrap_or_else(|error| {
+            eprintln!("error: could not open input: {}", error);
+            process::exit(1);
+        });
+
+    let mut vm = Vm::read(reader)
+        .unwrap_or_else(|errors| exit_with_errors(errors.iter().map(|error| error.render(&name))));
+
+    let accumulator_before_loop = vm.accumulator_before_loop().unwrap_or_else(|| {
+        eprintln!("error: the program does not loop");
+        process::exit(1);
+    });
 
     println!(
         "Accumulator of the VM before looping: {}",
-        vm.accumulator_before_loop(),
+        accumulator_before_loop,
     );
 
     vm.reset();
 
+    let accumulator_after_fixing_program =
+        vm.accumulator_after_fixing_program().unwrap_or_else(|| {
+            eprintln!("error: no single jmp or nop swap makes the program halt");
+            process::exit(1);
+        });
+
     println!(
         "Accumulator of the VM after fixing the program: {}",
-        vm.accumulator_after_fixing_program(),
+        accumulator_after_fixing_program,
     );
 }
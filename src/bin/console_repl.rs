@@ -0,0 +1,351 @@
+use aoc::console::{Breakpoint, Debugger, Instruction, Stop, Vm};
+use aoc::input::{exit_with_errors, Source};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::{env, process};
+
+const INPUT: &str = include_str!("console_input");
+
+const USAGE: &str = "usage: console_repl [FILE | -]";
+
+const HELP: &str = "\
+step [N]        run the next instruction, or the next N
+continue        run until a breakpoint, or until the program ends or loops
+break [BP]      stop before address BP, or once a condition such as
+                `acc > 5` becomes true; without BP, list the breakpoints
+delete BP       remove a breakpoint
+print acc|ip    print a register
+patch N INSTR   replace the instruction at address N, as in `patch 4 jmp +3`
+reset           go back to the start of the program
+disasm          list the whole program
+help            show this message
+quit            leave";
+
+const CONTEXT: usize = 2;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Step(usize),
+    Continue,
+    Break(Option<Breakpoint>),
+    Delete(Breakpoint),
+    Print(Register),
+    Patch(usize, Instruction),
+    Reset,
+    Disasm,
+    Help,
+    Quit,
+}
+
+#[derive(Debug, PartialEq)]
+enum Register {
+    Acc,
+    Ip,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = line
+            .split_once(' ')
+            .map_or((line, ""), |(name, argument)| (name, argument.trim()));
+
+        let command = match (name, argument) {
+            ("step" | "s", "") => Self::Step(1),
+            ("step" | "s", steps) => Self::Step(
+                steps
+                    .parse()
+                    .map_err(|_| format!("{} is not a number of steps", steps))?,
+            ),
+            ("continue" | "c", "") => Self::Continue,
+            ("break" | "b", "") => Self::Break(None),
+            ("break" | "b", breakpoint) => Self::Break(Some(parse_breakpoint(breakpoint)?)),
+            ("delete" | "d", breakpoint) if !breakpoint.is_empty() => {
+                Self::Delete(parse_breakpoint(breakpoint)?)
+            }
+            ("print" | "p", "acc") => Self::Print(Register::Acc),
+            ("print" | "p", "ip") => Self::Print(Register::Ip),
+            ("patch", argument) => {
+                let (address, instruction) = argument
+                    .split_once(' ')
+                    .ok_or("patch needs an address and an instruction")?;
+
+                let address = address
+                    .parse()
+                    .map_err(|_| format!("{} is not an address", address))?;
+                let instruction = instruction
+                    .trim()
+                    .parse()
+                    .map_err(|error| format!("invalid instruction: {}", error))?;
+
+                Self::Patch(address, instruction)
+            }
+            ("reset", "") => Self::Reset,
+            ("disasm", "") => Self::Disasm,
+            ("help" | "h", "") => Self::Help,
+            ("quit" | "q", "") => Self::Quit,
+            _ => return Err(format!("unknown command `{}`; try `help`", line)),
+        };
+
+        Ok(command)
+    }
+}
+
+fn parse_breakpoint(s: &str) -> Result<Breakpoint, String> {
+    s.parse()
+        .map_err(|error| format!("invalid breakpoint: {}", error))
+}
+
+fn main() {
+    if env::args_os().nth(2).is_some() {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+
+    let Source { name, mut reader } =
+        Source::from_args("console_input", INPUT).unwrap_or_else(|error| {
+            eprintln!("error: could not open input: {}", error);
+            process::exit(1);
+        });
+
+    // A program read from standard input ends at the first blank line, and
+    // the commands follow it.
+    let reads_stdin = env::args_os().nth(1).is_some_and(|path| path == "-");
+
+    let (vm, commands): (_, Box<dyn BufRead>) = if reads_stdin {
+        let program = read_program(&mut reader);
+        (Vm::read(program.as_bytes()), reader)
+    } else {
+        (Vm::read(reader), Box::new(io::stdin().lock()))
+    };
+
+    let vm = vm
+        .unwrap_or_else(|errors| exit_with_errors(errors.iter().map(|error| error.render(&name))));
+
+    let mut debugger = Debugger::new(vm);
+    show_context(&debugger);
+
+    let mut lines = commands.lines();
+
+    loop {
+        print!("(console) ");
+        io::stdout().flush().unwrap();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(error)) => {
+                eprintln!("error: {}", error);
+                process::exit(1);
+            }
+            None => {
+                println!();
+                break;
+            }
+        };
+
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        match line.parse() {
+            Ok(Command::Quit) => break,
+            Ok(command) => run(&mut debugger, command),
+            Err(error) => eprintln!("error: {}", error),
+        }
+    }
+}
+
+/// Reads up to the first blank line, leaving the rest in `reader`.
+fn read_program(reader: &mut impl BufRead) -> String {
+    let mut program = String::new();
+
+    for line in reader.lines() {
+        let line = line.unwrap_or_else(|error| {
+            eprintln!("error: could not read input: {}", error);
+            process::exit(1);
+        });
+
+        if line.trim().is_empty() {
+            break;
+        }
+
+        program.push_str(&line);
+        program.push('\n');
+    }
+
+    program
+}
+
+fn run(debugger: &mut Debugger, command: Command) {
+    match command {
+        Command::Step(steps) => {
+            let mut stop = Stop::Stepped;
+
+            for _ in 0..steps {
+                stop = debugger.step();
+
+                if stop != Stop::Stepped {
+                    break;
+                }
+            }
+
+            report(debugger, stop);
+        }
+        Command::Continue => {
+            let stop = debugger.resume();
+            report(debugger, stop);
+        }
+        Command::Break(None) if debugger.breakpoints().is_empty() => println!("no breakpoints"),
+        Command::Break(None) => {
+            for breakpoint in debugger.breakpoints() {
+                println!("{}", breakpoint);
+            }
+        }
+        Command::Break(Some(breakpoint)) => {
+            if debugger.add_breakpoint(breakpoint) {
+                println!("breakpoint {} set", breakpoint);
+            } else {
+                println!("breakpoint {} was already set", breakpoint);
+            }
+        }
+        Command::Delete(breakpoint) => {
+            if debugger.remove_breakpoint(breakpoint) {
+                println!("breakpoint {} removed", breakpoint);
+            } else {
+                eprintln!("error: breakpoint {} is not set", breakpoint);
+            }
+        }
+        Command::Print(Register::Acc) => println!("{}", debugger.vm().registers().acc),
+        Command::Print(Register::Ip) => println!("{}", debugger.vm().registers().ip),
+        Command::Patch(address, instruction) => {
+            let old_instruction = debugger.vm_mut().patch(address, instruction);
+
+            match old_instruction {
+                Some(old_instruction) => {
+                    println!("{}: {} is now {}", address, old_instruction, instruction)
+                }
+                None => eprintln!("error: there is no instruction at address {}", address),
+            }
+        }
+        Command::Reset => {
+            debugger.reset();
+            show_context(debugger);
+        }
+        Command::Disasm => {
+            for address in 0..debugger.vm().len() {
+                show_instruction(debugger, address);
+            }
+        }
+        Command::Help => println!("{}", HELP),
+        Command::Quit => unreachable!("quitting is handled by the caller"),
+    }
+}
+
+fn report(debugger: &Debugger, stop: Stop) {
+    match stop {
+        Stop::Stepped => {}
+        Stop::Breakpoint(breakpoint) => println!("stopped at breakpoint {}", breakpoint),
+        Stop::Ended(outcome) => println!("{}", outcome),
+    }
+
+    show_context(debugger);
+}
+
+fn show_context(debugger: &Debugger) {
+    let registers = debugger.vm().registers();
+    println!("{}", registers);
+
+    if registers.ip < 0 {
+        return;
+    }
+
+    let ip = registers.ip as usize;
+    let end = (ip + CONTEXT + 1).min(debugger.vm().len());
+
+    for address in ip.saturating_sub(CONTEXT)..end {
+        show_instruction(debugger, address);
+    }
+}
+
+fn show_instruction(debugger: &Debugger, address: usize) {
+    let vm = debugger.vm();
+    let instruction = vm.instruction(address).unwrap();
+
+    let pointer = if vm.registers().ip == address as i64 {
+        "->"
+    } else {
+        "  "
+    };
+
+    let breakpoint = if debugger
+        .breakpoints()
+        .contains(&Breakpoint::Address(address))
+    {
+        '*'
+    } else {
+        ' '
+    };
+
+    println!(
+        "{}{}{:>5}: {:<8} runs: {}",
+        pointer,
+        breakpoint,
+        address,
+        instruction.to_string(),
+        vm.hit_count(address).unwrap(),
+    );
+}
+
+#[cfg(test)]
+mod command_tests {
+    use super::*;
+    use aoc::console::Comparison;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!("step".parse(), Ok(Command::Step(1)));
+        assert_eq!("s 3".parse(), Ok(Command::Step(3)));
+        assert_eq!("break".parse(), Ok(Command::Break(None)));
+        assert_eq!("print ip".parse(), Ok(Command::Print(Register::Ip)));
+    }
+
+    #[test]
+    fn parse_patch() {
+        assert_eq!(
+            "patch 4 jmp +3".parse(),
+            Ok(Command::Patch(4, "jmp +3".parse().unwrap())),
+        );
+    }
+
+    #[test]
+    fn parse_accumulator_breakpoint() {
+        assert_eq!(
+            "break acc >= 2".parse(),
+            Ok(Command::Break(Some(Breakpoint::Accumulator(
+                Comparison::GreaterOrEqual,
+                2,
+            )))),
+        );
+    }
+
+    #[test]
+    fn reject_malformed_commands() {
+        for command in &[
+            "frobnicate",
+            "step x",
+            "continue 2",
+            "break acc = 2",
+            "delete",
+            "print sp",
+            "patch 4",
+            "patch x jmp +3",
+            "patch 4 jmp",
+            "patch 4 hop +3",
+        ] {
+            assert!(command.parse::<Command>().is_err(), "{}", command);
+        }
+    }
+}
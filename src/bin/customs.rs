@@ -1,12 +1,28 @@
 use aoc::customs::Group;
+use aoc::input::Source;
+use std::process;
 
 const INPUT: &str = include_str!("customs_input");
 
 fn main() {
-    let groups: Vec<_> = INPUT.split("\n\n").map(Group::from).collect();
+    let Source { name, reader } =
+        Source::from_args("customs_input", INPUT).unwrap_or_else(|error| {
+            eprintln!("error: could not open input: {}", error);
+            process::exit(1);
+        });
 
-    let num_questions: usize = groups.iter().map(Group::num_questions).sum();
-    let num_questions_all_have: usize = groups.iter().map(Group::num_questions_all_have).sum();
+    let mut num_questions = 0;
+    let mut num_questions_all_have = 0;
+
+    for group in Group::read_all(reader) {
+        let group = group.unwrap_or_else(|error| {
+            eprintln!("{}", error.render(&name));
+            process::exit(1);
+        });
+
+        num_questions += group.num_questions();
+        num_questions_all_have += group.num_questions_all_have();
+    }
 
     println!(
         "Number of questions to which anyone in a group answered ‘yes’: {}",
@@ -1,13 +1,18 @@
 use aoc::expense_report;
-use std::str::FromStr;
+use aoc::input::exit_with_errors;
+use aoc::parsing::{all_consuming, parse_all_lines, unsigned};
 
 const INPUT: &str = include_str!("expense_report_input");
 
 fn main() {
-    let expenses: Vec<_> = INPUT
-        .lines()
-        .map(|line| u32::from_str(line).unwrap())
-        .collect();
+    let expenses =
+        parse_all_lines(INPUT, |line| all_consuming(unsigned, line)).unwrap_or_else(|errors| {
+            exit_with_errors(
+                errors
+                    .iter()
+                    .map(|error| error.render("expense_report_input", INPUT)),
+            )
+        });
 
     let format_solution = |s: Option<u32>| s.map_or("No solution".to_string(), |n| n.to_string());
 
@@ -1,4 +1,5 @@
 use aoc::geology::{Map, Slope};
+use std::process;
 use std::str::FromStr;
 
 const INPUT: &str = include_str!("geology_input");
@@ -12,7 +13,10 @@ const SLOPES: &[Slope] = &[
 ];
 
 fn main() {
-    let map = Map::from_str(INPUT).unwrap();
+    let map = Map::from_str(INPUT).unwrap_or_else(|error| {
+        eprintln!("{}", error.render("geology_input", INPUT));
+        process::exit(1);
+    });
 
     println!(
         "Number of trees in path with slope of ‘right 3, down 1’: {}",
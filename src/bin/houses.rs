@@ -3,7 +3,7 @@ use aoc::houses::Moves;
 const INPUT: &str = include_str!("houses_input");
 
 fn main() {
-    let moves = Moves::new(INPUT.trim()).unwrap();
+    let moves = Moves::new(INPUT).unwrap();
 
     println!(
         "without Robo-Santa: {}",
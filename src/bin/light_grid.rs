@@ -1,19 +1,37 @@
-use aoc::light_grid::{BinaryLight, Grid, Instruction, Light, ScalarLight};
-use std::str::FromStr;
+use aoc::input::Source;
+use aoc::light_grid::{BinaryLight, Grid, Instruction, ScalarLight};
+use std::process;
 
 const INPUT: &str = include_str!("light_grid_input");
 
 fn main() {
-    calculate_total_brightness::<BinaryLight>("With binary lights");
-    calculate_total_brightness::<ScalarLight>("With scalar lights");
-}
+    let Source { name, reader } =
+        Source::from_args("light_grid_input", INPUT).unwrap_or_else(|error| {
+            eprintln!("error: could not open input: {}", error);
+            process::exit(1);
+        });
+
+    let mut binary_grid: Grid<BinaryLight> = Grid::default();
+    let mut scalar_grid: Grid<ScalarLight> = Grid::default();
+    let mut failed = false;
 
-fn calculate_total_brightness<L: Light>(msg: &str) {
-    let mut grid: Grid<L> = Grid::default();
+    for instruction in Instruction::read_all(reader) {
+        match instruction {
+            Ok(instruction) => {
+                binary_grid.apply(instruction);
+                scalar_grid.apply(instruction);
+            }
+            Err(error) => {
+                eprintln!("{}\n", error.render(&name));
+                failed = true;
+            }
+        }
+    }
 
-    for instruction in INPUT.lines().map(Instruction::from_str) {
-        grid.apply(instruction.unwrap());
+    if failed {
+        process::exit(1);
     }
 
-    println!("{}: {}", msg, grid.total_brightness());
+    println!("With binary lights: {}", binary_grid.total_brightness());
+    println!("With scalar lights: {}", scalar_grid.total_brightness());
 }
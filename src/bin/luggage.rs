@@ -1,4 +1,5 @@
 use aoc::luggage::{Bag, Rules};
+use std::process;
 use std::str::FromStr;
 
 const INPUT: &str = include_str!("luggage_input");
@@ -9,15 +10,23 @@ fn main() {
         color: "gold".to_string(),
     };
 
-    let rules = Rules::from_str(INPUT).unwrap();
+    let rules = Rules::from_str(INPUT).unwrap_or_else(|error| {
+        eprintln!("{}", error.render("luggage_input", INPUT));
+        process::exit(1);
+    });
 
     println!(
         "Number of bags that can contain a shiny gold bag: {}",
         rules.num_bags_that_can_contain(&bag),
     );
 
+    let num_bags_contained = rules.num_bags_contained_by(&bag).unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    });
+
     println!(
         "Number of bags that are contained by a shiny gold bag: {}",
-        rules.num_bags_contained_by(&bag),
+        num_bags_contained,
     );
 }
@@ -1,16 +1,17 @@
 use aoc::naughty_or_nice_strings::{Judgement, Ruleset};
+use aoc::parsing::lines;
 
 const INPUT: &str = include_str!("naughty_or_nice_strings_input");
 
 fn main() {
     println!(
         "With old ruleset: {}",
-        num_nice_lines(INPUT.trim().lines(), Ruleset::Old),
+        num_nice_lines(lines(INPUT), Ruleset::Old),
     );
 
     println!(
         "With new ruleset: {}",
-        num_nice_lines(INPUT.trim().lines(), Ruleset::New),
+        num_nice_lines(lines(INPUT), Ruleset::New),
     );
 }
 
@@ -1,19 +1,41 @@
+use aoc::input::Source;
 use aoc::passport::Passport;
+use std::process;
 
 const INPUT: &str = include_str!("passport_input");
 
 fn main() {
-    let passports: Vec<_> = INPUT.split("\n\n").map(Passport::from).collect();
+    let Source { name, reader } =
+        Source::from_args("passport_input", INPUT).unwrap_or_else(|error| {
+            eprintln!("error: could not open input: {}", error);
+            process::exit(1);
+        });
 
-    let num_complete_passports = passports
-        .iter()
-        .filter(|passport| passport.is_complete())
-        .count();
+    let mut num_complete_passports = 0;
+    let mut num_valid_passports = 0;
+    let mut failed = false;
 
-    let num_valid_passports = passports
-        .iter()
-        .filter(|passport| passport.is_valid())
-        .count();
+    for passport in Passport::read_all(reader) {
+        match passport {
+            Ok(passport) => {
+                if passport.is_complete() {
+                    num_complete_passports += 1;
+                }
+
+                if passport.is_valid() {
+                    num_valid_passports += 1;
+                }
+            }
+            Err(error) => {
+                eprintln!("{}\n", error.render(&name));
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
 
     println!("Number of complete passports: {}", num_complete_passports);
     println!("Number of valid passports: {}", num_valid_passports);
@@ -1,17 +1,26 @@
+use aoc::input::exit_with_errors;
+use aoc::parsing::parse_all_lines;
 use aoc::password::{Password, Ruleset};
 use std::convert::TryFrom;
 
 const INPUT: &str = include_str!("password_input");
 
 fn main() {
-    let passwords = INPUT.lines().map(|line| Password::try_from(line).unwrap());
+    let passwords: Vec<_> = parse_all_lines(INPUT, Password::try_from).unwrap_or_else(|errors| {
+        exit_with_errors(
+            errors
+                .iter()
+                .map(|error| error.render("password_input", INPUT)),
+        )
+    });
 
     let num_valid_with_old_ruleset = passwords
-        .clone()
+        .iter()
         .filter(|password| password.is_valid(Ruleset::Old))
         .count();
 
     let num_valid_with_new_ruleset = passwords
+        .iter()
         .filter(|password| password.is_valid(Ruleset::New))
         .count();
 
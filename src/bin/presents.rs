@@ -1,20 +1,24 @@
+use aoc::input::exit_with_errors;
+use aoc::parsing::parse_all_lines;
 use aoc::presents::Present;
-use std::str::FromStr;
 
 const INPUT: &str = include_str!("presents_input");
 
 fn main() {
-    let presents = INPUT
-        .trim()
-        .lines()
-        .map(|line| Present::from_str(line).unwrap());
+    let presents: Vec<Present> = parse_all_lines(INPUT, str::parse).unwrap_or_else(|errors| {
+        exit_with_errors(
+            errors
+                .iter()
+                .map(|error| error.render("presents_input", INPUT)),
+        )
+    });
 
     let total_wrapping_paper: u32 = presents
-        .clone()
+        .iter()
         .map(|present| present.wrapping_paper_area())
         .sum();
 
-    let total_ribbon_length: u32 = presents.map(|present| present.ribbon_length()).sum();
+    let total_ribbon_length: u32 = presents.iter().map(|present| present.ribbon_length()).sum();
 
     println!("{}", total_wrapping_paper);
     println!("{}", total_ribbon_length);
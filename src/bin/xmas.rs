@@ -1,10 +1,25 @@
+use aoc::input::{exit_with_errors, Source};
 use aoc::xmas::Input;
+use std::process;
 
 const INPUT: &str = include_str!("xmas_input");
 
 fn main() {
-    let input = Input::new(INPUT);
+    let Source { name, reader } = Source::from_args("xmas_input", INPUT).unwrap_or_else(|error| {
+        eprintln!("error: could not open input: {}", error);
+        process::exit(1);
+    });
 
-    println!("Smallest invalid number: {}", input.first_invalid_number());
-    println!("Encryption weakness: {}", input.determine_weakness());
+    let input = Input::read(reader)
+        .unwrap_or_else(|errors| exit_with_errors(errors.iter().map(|error| error.render(&name))));
+
+    match input.first_invalid_number() {
+        Some(number) => println!("Smallest invalid number: {}", number),
+        None => println!("Every number is valid"),
+    }
+
+    match input.determine_weakness() {
+        Some(weakness) => println!("Encryption weakness: {}", weakness),
+        None => println!("No encryption weakness was found"),
+    }
 }
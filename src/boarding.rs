@@ -1,9 +1,15 @@
+use crate::parsing::{all_consuming, alt, tag, ParseError};
 use std::ops::Range;
+use std::str::FromStr;
 
 const NUM_ROWS: u32 = 127;
 const NUM_COLUMNS: u32 = 7;
 
-pub fn find_seat(seat_locations: &[SeatLocation]) -> SeatLocation {
+// Enough partitions to narrow the rows and columns down to one of each.
+const ROW_PARTITIONS: usize = 7;
+const COLUMN_PARTITIONS: usize = 3;
+
+pub fn find_seat(seat_locations: &[SeatLocation]) -> Option<SeatLocation> {
     let mut all_possible_locations: Vec<_> = (1..NUM_ROWS)
         .flat_map(|row| (1..NUM_COLUMNS).map(move |column| SeatLocation { row, column }))
         .collect();
@@ -19,14 +25,16 @@ pub fn find_seat(seat_locations: &[SeatLocation]) -> SeatLocation {
         let have_location_with_id_minus_one = seat_locations
             .iter()
             .map(SeatLocation::id)
-            .any(|id| id - 1 == location.id());
+            .any(|id| id == location.id() + 1);
 
         !have_current_location && have_location_with_id_plus_one && have_location_with_id_minus_one
     });
 
-    assert_eq!(all_possible_locations.len(), 1);
-
-    all_possible_locations[0]
+    if all_possible_locations.len() == 1 {
+        all_possible_locations.pop()
+    } else {
+        None
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -41,6 +49,7 @@ impl SeatLocation {
     }
 }
 
+#[derive(Debug)]
 pub struct BoardingPass {
     partitions: Vec<Partition>,
 }
@@ -71,11 +80,31 @@ impl BoardingPass {
     }
 }
 
-impl From<&str> for BoardingPass {
-    fn from(s: &str) -> Self {
-        Self {
-            partitions: s.chars().map(|c| c.into()).collect(),
+impl BoardingPass {
+    fn new(mut s: &str) -> Result<(&str, Self), ParseError> {
+        let mut partitions = Vec::with_capacity(ROW_PARTITIONS + COLUMN_PARTITIONS);
+
+        for _ in 0..ROW_PARTITIONS {
+            let (new_s, partition) = Partition::new_row(s)?;
+            s = new_s;
+            partitions.push(partition);
+        }
+
+        for _ in 0..COLUMN_PARTITIONS {
+            let (new_s, partition) = Partition::new_column(s)?;
+            s = new_s;
+            partitions.push(partition);
         }
+
+        Ok((s, Self { partitions }))
+    }
+}
+
+impl FromStr for BoardingPass {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
     }
 }
 
@@ -103,15 +132,52 @@ enum Partition {
     Right,
 }
 
-impl From<char> for Partition {
-    fn from(c: char) -> Self {
-        match c {
-            'F' => Self::Front,
-            'B' => Self::Back,
-            'L' => Self::Left,
-            'R' => Self::Right,
-            _ => panic!("invalid input"),
-        }
+impl Partition {
+    fn new_row(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                |s| tag("F", s).map(|s| (s, Self::Front)),
+                |s| tag("B", s).map(|s| (s, Self::Back)),
+            ),
+            s,
+        )
+    }
+
+    fn new_column(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                |s| tag("L", s).map(|s| (s, Self::Left)),
+                |s| tag("R", s).map(|s| (s, Self::Right)),
+            ),
+            s,
+        )
+    }
+}
+
+#[cfg(test)]
+mod parsing_tests {
+    use super::*;
+
+    #[test]
+    fn parse_boarding_pass() {
+        let boarding_pass: BoardingPass = "FBFBBFFRLR".parse().unwrap();
+        assert_eq!(
+            boarding_pass.location(),
+            SeatLocation { row: 44, column: 5 }
+        );
+    }
+
+    #[test]
+    fn reject_column_partition_in_row_position() {
+        let error = "FBFLBFFRLR".parse::<BoardingPass>().unwrap_err();
+
+        assert_eq!(error.column(), 4);
+        assert_eq!(error.expected().collect::<Vec<_>>(), [r#""F""#, r#""B""#]);
+    }
+
+    #[test]
+    fn reject_short_boarding_pass() {
+        assert!("FBFBBFFRL".parse::<BoardingPass>().is_err());
     }
 }
 
@@ -1,13 +1,18 @@
-use std::num::ParseIntError;
+use crate::parsing::{all_consuming, in_range, parse_lines, unsigned, ParseError};
 use std::str::FromStr;
 
+// The device’s built-in adapter is rated this many jolts higher than the
+// highest-rated adapter.
+const DEVICE_JOLTS_ABOVE_HIGHEST: u32 = 3;
+
 #[derive(Debug)]
 pub struct Adapters {
     adapters: Vec<Adapter>,
 }
 
 impl Adapters {
-    pub fn differences_to_outlet(&mut self) -> Vec<Difference> {
+    /// `None` if two adapters differ by anything other than one or three jolts.
+    pub fn differences_to_outlet(&mut self) -> Option<Vec<Difference>> {
         self.adapters.sort();
 
         self.adapters
@@ -17,9 +22,9 @@ impl Adapters {
                 let current_adapter = &adapters[1];
 
                 match current_adapter.jolts - last_adapter.jolts {
-                    1 => Difference::One,
-                    3 => Difference::Three,
-                    _ => unreachable!(),
+                    1 => Some(Difference::One),
+                    3 => Some(Difference::Three),
+                    _ => None,
                 }
             })
             .collect()
@@ -27,19 +32,24 @@ impl Adapters {
 }
 
 impl FromStr for Adapters {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut adapters: Vec<_> = std::iter::once(Ok(Adapter { jolts: 0 }))
-            .chain(s.lines().map(|line| {
-                Ok(Adapter {
-                    jolts: line.parse()?,
-                })
+            .chain(parse_lines(s, |line| {
+                let jolts = all_consuming(
+                    |s| in_range(unsigned, ..=u32::MAX - DEVICE_JOLTS_ABOVE_HIGHEST, s),
+                    line,
+                )?;
+
+                Ok(Adapter { jolts })
             }))
             .collect::<Result<_, _>>()?;
 
+        let highest_jolts = adapters.iter().map(|adapter| adapter.jolts).max();
+
         adapters.push(Adapter {
-            jolts: adapters.iter().max().unwrap().jolts + 3,
+            jolts: highest_jolts.unwrap_or(0) + DEVICE_JOLTS_ABOVE_HIGHEST,
         });
 
         Ok(Self { adapters })
@@ -79,7 +89,7 @@ mod tests {
         .unwrap();
 
         assert_eq!(
-            adapters.differences_to_outlet(),
+            adapters.differences_to_outlet().unwrap(),
             vec![
                 Difference::One,
                 Difference::Three,
@@ -96,4 +106,21 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn gap_too_large_to_chain() {
+        let mut adapters = Adapters::from_str("1\n5").unwrap();
+        assert_eq!(adapters.differences_to_outlet(), None);
+    }
+
+    #[test]
+    fn gap_of_two_jolts() {
+        let mut adapters = Adapters::from_str("1\n3").unwrap();
+        assert_eq!(adapters.differences_to_outlet(), None);
+    }
+
+    #[test]
+    fn reject_adapter_with_no_room_for_device() {
+        assert!(Adapters::from_str("4294967295").is_err());
+    }
 }
@@ -1,84 +1,178 @@
-use crate::parsing::{extract_digits, extract_lowercase, tag};
-use std::collections::HashMap;
+use crate::parsing::{
+    all_consuming, alt, extract_lowercase, map, preceded, tag, unsigned, ParseError,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter;
 use std::str::FromStr;
 
+mod analysis;
+mod bdd;
+mod dot;
+mod equivalence;
+mod netlist;
+mod plan;
+mod simplify;
+mod simulate;
+mod symbolic;
+mod verilog;
+mod word;
+
+pub use analysis::{Analysis, WireAnalysis};
+pub use dot::Dot;
+pub use equivalence::{Counterexample, Equivalence, EquivalenceError};
+use netlist::Netlist;
+pub use plan::{Plan, Signals};
+pub use simplify::SimplifyReport;
+pub use simulate::{Settling, Simulation};
+pub use symbolic::{Expr, SearchSpaceTooLarge, Symbolic, MAX_SEARCH_BITS};
+pub use verilog::Verilog;
+pub use word::{Bits, Word};
+
 #[derive(Debug)]
 pub struct Circuit {
     pub instructions: Vec<Instruction>,
 }
 
 impl Circuit {
-    pub fn emulate(&self) -> HashMap<&str, u16> {
-        let instructions = self
-            .instructions
-            .iter()
-            .map(|Instruction { wire, signal }| (wire.as_str(), signal.clone()))
-            .collect();
+    pub fn emulate(&self) -> Result<HashMap<&str, u16>, Vec<CircuitError>> {
+        Ok(self.compile()?.emulate())
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<CircuitError>> {
+        Netlist::new(self).validate().map(|_| ())
+    }
+
+    pub fn compile(&self) -> Result<Plan<'_>, Vec<CircuitError>> {
+        self.compile_as()
+    }
+
+    /// Every literal must fit in `W`.
+    pub fn compile_as<W: Word>(&self) -> Result<Plan<'_, W>, Vec<CircuitError>> {
+        let (netlist, order) = self.validate_as::<W>()?;
 
-        let mut cache = HashMap::with_capacity(self.instructions.len());
+        Ok(Plan::new(netlist, order))
+    }
+
+    fn validate_as<W: Word>(&self) -> Result<(Netlist<'_>, Vec<usize>), Vec<CircuitError>> {
+        let netlist = Netlist::new(self);
+
+        match netlist.validate() {
+            Ok(order) => {
+                let errors = self.too_wide_literals::<W>();
+
+                if errors.is_empty() {
+                    Ok((netlist, order))
+                } else {
+                    Err(errors)
+                }
+            }
+            Err(mut errors) => {
+                errors.extend(self.too_wide_literals::<W>());
+                Err(errors)
+            }
+        }
+    }
+
+    fn too_wide_literals<W: Word>(&self) -> Vec<CircuitError> {
+        let mut seen = HashSet::new();
 
         self.instructions
             .iter()
-            .map(|Instruction { wire, signal }| {
-                (wire.as_str(), signal.emulate(&instructions, &mut cache))
+            .flat_map(|instruction| instruction.signal.literals())
+            .filter(|&literal| literal > word::mask(W::BITS) && seen.insert(literal))
+            .map(|literal| CircuitError::LiteralTooWide {
+                literal,
+                bits: W::BITS,
             })
             .collect()
     }
 
-    pub fn override_and_clear(
+    pub fn emulate_with_overrides(
         &self,
-        wire_signals: HashMap<&str, u16>,
-        from: &str,
-        to: &str,
-    ) -> u16 {
-        let instructions = self
-            .instructions
-            .iter()
-            .map(|Instruction { wire, signal }| (wire.as_str(), signal.clone()))
-            .collect();
+        overrides: &[(&str, u16)],
+    ) -> Result<HashMap<&str, u16>, Vec<CircuitError>> {
+        self.compile()?
+            .signals()
+            .with_overrides(overrides)
+            .map(|signals| signals.to_map())
+            .map_err(|error| vec![error])
+    }
 
-        let mut cache = {
-            let from_value = *wire_signals.get(from).unwrap();
-            let mut cache = HashMap::with_capacity(wire_signals.len());
-            cache.insert(to, from_value);
-            cache
-        };
+    pub fn to_dot(&self) -> Dot<'_> {
+        Dot::new(self)
+    }
+}
 
-        self.instructions
-            .iter()
-            .find_map(|Instruction { wire, signal }| {
-                if wire == from {
-                    Some(signal.emulate(&instructions, &mut cache))
-                } else {
-                    None
-                }
-            })
-            .unwrap()
+impl fmt::Display for Circuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+
+        Ok(())
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum CircuitError {
+    UndefinedWire(String),
+    DrivenTwice(String),
+    Cycle(Vec<String>),
+    LiteralTooWide { literal: u64, bits: u32 },
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndefinedWire(wire) => write!(f, "wire {} is used but never driven", wire),
+            Self::DrivenTwice(wire) => write!(f, "wire {} is driven more than once", wire),
+            Self::Cycle(wires) if wires.len() == 1 => {
+                write!(f, "wire {} depends on itself", wires[0])
+            }
+            Self::Cycle(wires) => write!(f, "wires {} form a cycle", wires.join(", ")),
+            Self::LiteralTooWide { literal, bits } => {
+                write!(f, "literal {} does not fit in {} bits", literal, bits)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
 #[derive(Debug, PartialEq)]
 pub struct Instruction {
     signal: Signal,
     wire: String,
 }
 
-impl FromStr for Instruction {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Instruction {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
         let (s, signal) = Signal::new(s)?;
         let s = tag(" -> ", s)?;
         let (s, wire) = extract_lowercase(s)?;
 
-        if !s.is_empty() {
-            return Err("parser did not consume entire input".to_string());
-        }
+        Ok((
+            s,
+            Self {
+                signal,
+                wire: wire.to_string(),
+            },
+        ))
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
 
-        Ok(Self {
-            signal,
-            wire: wire.to_string(),
-        })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.signal, self.wire)
     }
 }
 
@@ -96,13 +190,11 @@ enum Signal {
 }
 
 impl Signal {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_bin_op(s)
-            .or_else(|_| Self::new_not(s))
-            .or_else(|_| Self::new_single(s))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt((Self::new_bin_op, Self::new_not, Self::new_single), s)
     }
 
-    fn new_bin_op(s: &str) -> Result<(&str, Self), String> {
+    fn new_bin_op(s: &str) -> Result<(&str, Self), ParseError> {
         let (s, lhs) = SingleSignal::new(s)?;
         let s = tag(" ", s)?;
 
@@ -114,102 +206,213 @@ impl Signal {
         Ok((s, Self::WithGate { lhs, rhs, gate: op }))
     }
 
-    fn new_not(s: &str) -> Result<(&str, Self), String> {
-        let s = tag("NOT ", s)?;
-        let (s, signal) = SingleSignal::new(s)?;
+    fn new_not(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, signal) = preceded(|s| tag("NOT ", s), SingleSignal::new, s)?;
 
         Ok((s, Self::Not { signal }))
     }
 
-    fn new_single(s: &str) -> Result<(&str, Self), String> {
+    fn new_single(s: &str) -> Result<(&str, Self), ParseError> {
         let (s, single_signal) = SingleSignal::new(s)?;
         Ok((s, Self::Single(single_signal)))
     }
 
-    fn emulate<'a>(
-        &'a self,
-        wire_signals: &'a HashMap<&'a str, Signal>,
-        cache: &mut HashMap<&'a str, u16>,
-    ) -> u16 {
+    fn wires(&self) -> impl Iterator<Item = &str> {
+        self.operands().filter_map(SingleSignal::wire)
+    }
+
+    fn literals(&self) -> impl Iterator<Item = u64> + '_ {
+        self.operands().filter_map(SingleSignal::literal)
+    }
+
+    fn operands(&self) -> impl Iterator<Item = &SingleSignal> {
+        let (first, second) = match self {
+            Self::WithGate { lhs, rhs, .. } => (lhs, Some(rhs)),
+            Self::Not { signal } => (signal, None),
+            Self::Single(single_signal) => (single_signal, None),
+        };
+
+        iter::once(first).chain(second)
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Signal::WithGate { lhs, rhs, gate } => gate.apply_to(
-                lhs.emulate(wire_signals, cache),
-                rhs.emulate(wire_signals, cache),
-            ),
-            Signal::Not { signal } => !signal.emulate(wire_signals, cache),
-            Signal::Single(single_signal) => single_signal.emulate(wire_signals, cache),
+            Self::WithGate { lhs, rhs, gate } => write!(f, "{} {} {}", lhs, gate, rhs),
+            Self::Not { signal } => write!(f, "NOT {}", signal),
+            Self::Single(single_signal) => write!(f, "{}", single_signal),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 enum SingleSignal {
-    Literal(u16),
+    Literal(u64),
     Wire(String),
 }
 
 impl SingleSignal {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_literal(s).or_else(|_| Self::new_wire(s))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt((Self::new_literal, Self::new_wire), s)
     }
 
-    fn new_literal(s: &str) -> Result<(&str, Self), String> {
-        let (s, literal) = extract_digits(s)?;
-        Ok((s, Self::Literal(literal.parse().unwrap())))
+    fn wire(&self) -> Option<&str> {
+        match self {
+            Self::Literal(_) => None,
+            Self::Wire(wire) => Some(wire),
+        }
     }
 
-    fn new_wire(s: &str) -> Result<(&str, Self), String> {
-        let (s, wire) = extract_lowercase(s)?;
-        Ok((s, Self::Wire(wire.to_string())))
+    fn literal(&self) -> Option<u64> {
+        match self {
+            Self::Literal(n) => Some(*n),
+            Self::Wire(_) => None,
+        }
     }
 
-    fn emulate<'a>(
-        &'a self,
-        wire_signals: &'a HashMap<&'a str, Signal>,
-        cache: &mut HashMap<&'a str, u16>,
-    ) -> u16 {
-        match self {
-            SingleSignal::Literal(n) => *n,
-            SingleSignal::Wire(wire) => cache.get(wire.as_str()).copied().unwrap_or_else(|| {
-                let emulated_signal = wire_signals
-                    .get(wire.as_str())
-                    .unwrap()
-                    .emulate(wire_signals, cache);
-                cache.insert(wire.as_str(), emulated_signal);
+    fn new_literal(s: &str) -> Result<(&str, Self), ParseError> {
+        map(unsigned, Self::Literal, s)
+    }
 
-                emulated_signal
-            }),
+    fn new_wire(s: &str) -> Result<(&str, Self), ParseError> {
+        map(extract_lowercase, |wire| Self::Wire(wire.to_string()), s)
+    }
+}
+
+impl fmt::Display for SingleSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(n) => write!(f, "{}", n),
+            Self::Wire(wire) => write!(f, "{}", wire),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum Gate {
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Gate {
     And,
     Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
     LShift,
     RShift,
+    LRotate,
+    RRotate,
+    Add,
+    Sub,
+    Mul,
 }
 
 impl Gate {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        tag("AND", s)
-            .map(|s| (s, Self::And))
-            .or_else(|_| tag("OR", s).map(|s| (s, Self::Or)))
-            .or_else(|_| tag("LSHIFT", s).map(|s| (s, Self::LShift)))
-            .or_else(|_| tag("RSHIFT", s).map(|s| (s, Self::RShift)))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                |s| tag("AND", s).map(|s| (s, Self::And)),
+                |s| tag("OR", s).map(|s| (s, Self::Or)),
+                |s| tag("XOR", s).map(|s| (s, Self::Xor)),
+                |s| tag("NAND", s).map(|s| (s, Self::Nand)),
+                |s| tag("NOR", s).map(|s| (s, Self::Nor)),
+                |s| tag("XNOR", s).map(|s| (s, Self::Xnor)),
+                |s| tag("LSHIFT", s).map(|s| (s, Self::LShift)),
+                |s| tag("RSHIFT", s).map(|s| (s, Self::RShift)),
+                |s| tag("LROTATE", s).map(|s| (s, Self::LRotate)),
+                |s| tag("RROTATE", s).map(|s| (s, Self::RRotate)),
+                |s| tag("ADD", s).map(|s| (s, Self::Add)),
+                |s| tag("SUB", s).map(|s| (s, Self::Sub)),
+                |s| tag("MUL", s).map(|s| (s, Self::Mul)),
+            ),
+            s,
+        )
     }
 
-    fn apply_to(&self, x: u16, y: u16) -> u16 {
-        match self {
+    /// Shifting by the bus width or more gives zero; arithmetic wraps.
+    pub fn apply_to(&self, x: u64, y: u64, bits: u32) -> u64 {
+        let rotation = (y % u64::from(bits)) as u32;
+
+        let result = match self {
             Self::And => x & y,
             Self::Or => x | y,
-            Self::LShift => x << y,
-            Self::RShift => x >> y,
+            Self::Xor => x ^ y,
+            Self::Nand => !(x & y),
+            Self::Nor => !(x | y),
+            Self::Xnor => !(x ^ y),
+            Self::LShift if y < u64::from(bits) => x << y,
+            Self::RShift if y < u64::from(bits) => x >> y,
+            Self::LShift | Self::RShift => 0,
+            Self::LRotate => x << rotation | x.checked_shr(bits - rotation).unwrap_or(0),
+            Self::RRotate => x >> rotation | x.checked_shl(bits - rotation).unwrap_or(0),
+            Self::Add => x.wrapping_add(y),
+            Self::Sub => x.wrapping_sub(y),
+            Self::Mul => x.wrapping_mul(y),
+        };
+
+        result & word::mask(bits)
+    }
+
+    fn is_commutative(&self) -> bool {
+        !matches!(
+            self,
+            Self::LShift | Self::RShift | Self::LRotate | Self::RRotate | Self::Sub,
+        )
+    }
+}
+
+impl FromStr for Gate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
+    }
+}
+
+impl fmt::Display for Gate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gate = match self {
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Xor => "XOR",
+            Self::Nand => "NAND",
+            Self::Nor => "NOR",
+            Self::Xnor => "XNOR",
+            Self::LShift => "LSHIFT",
+            Self::RShift => "RSHIFT",
+            Self::LRotate => "LROTATE",
+            Self::RRotate => "RROTATE",
+            Self::Add => "ADD",
+            Self::Sub => "SUB",
+            Self::Mul => "MUL",
+        };
+
+        write!(f, "{}", gate)
+    }
+}
+
+pub fn wire_name(mut idx: usize) -> String {
+    let mut name = String::new();
+
+    loop {
+        name.push((b'a' + (idx % 26) as u8) as char);
+        idx /= 26;
+
+        if idx == 0 {
+            return name;
         }
     }
 }
 
+#[cfg(test)]
+fn circuit(instructions: &[&str]) -> Circuit {
+    Circuit {
+        instructions: instructions
+            .iter()
+            .map(|instruction| instruction.parse().unwrap())
+            .collect(),
+    }
+}
+
 #[cfg(test)]
 mod emulation_tests {
     use super::*;
@@ -229,7 +432,7 @@ mod emulation_tests {
             ],
         };
 
-        let wire_signals = circuit.emulate();
+        let wire_signals = circuit.emulate().unwrap();
 
         assert_eq!(
             {
@@ -256,7 +459,7 @@ mod emulation_tests {
             instructions: vec!["a -> b".parse().unwrap(), "10 -> a".parse().unwrap()],
         };
 
-        let wire_signals = circuit.emulate();
+        let wire_signals = circuit.emulate().unwrap();
 
         assert_eq!(
             {
@@ -269,6 +472,279 @@ mod emulation_tests {
             wire_signals,
         );
     }
+
+    #[test]
+    fn emulate_long_chain_without_recursing() {
+        let instructions = iter::once("1 -> a".parse().unwrap())
+            .chain((1..100_000).map(|idx| {
+                format!("NOT {} -> {}", wire_name(idx - 1), wire_name(idx))
+                    .parse()
+                    .unwrap()
+            }))
+            .collect();
+
+        let circuit = Circuit { instructions };
+        let wire_signals = circuit.emulate().unwrap();
+
+        assert_eq!(wire_signals[wire_name(99_999).as_str()], !1);
+    }
+
+    #[test]
+    fn override_wire() {
+        let circuit = Circuit {
+            instructions: vec![
+                "1 -> b".parse().unwrap(),
+                "b LSHIFT 1 -> a".parse().unwrap(),
+                "3 -> c".parse().unwrap(),
+                "c OR a -> d".parse().unwrap(),
+            ],
+        };
+
+        let wire_signals = circuit.emulate_with_overrides(&[("b", 2)]).unwrap();
+
+        assert_eq!(
+            {
+                let mut signals = HashMap::new();
+                signals.insert("a", 4);
+                signals.insert("b", 2);
+                signals.insert("c", 3);
+                signals.insert("d", 7);
+
+                signals
+            },
+            wire_signals,
+        );
+    }
+
+    #[test]
+    fn override_wire_that_has_inputs() {
+        let circuit = Circuit {
+            instructions: vec![
+                "1 -> b".parse().unwrap(),
+                "b LSHIFT 1 -> a".parse().unwrap(),
+                "NOT a -> c".parse().unwrap(),
+            ],
+        };
+
+        let wire_signals = circuit.emulate_with_overrides(&[("a", 0)]).unwrap();
+
+        assert_eq!(wire_signals["b"], 1);
+        assert_eq!(wire_signals["a"], 0);
+        assert_eq!(wire_signals["c"], 0xFFFF);
+    }
+
+    #[test]
+    fn keep_earlier_overrides() {
+        let circuit = Circuit {
+            instructions: vec![
+                "1 -> x".parse().unwrap(),
+                "x -> y".parse().unwrap(),
+                "y AND z -> w".parse().unwrap(),
+                "1 -> z".parse().unwrap(),
+            ],
+        };
+
+        let plan = circuit.compile().unwrap();
+        let signals = plan.signals();
+        assert_eq!(signals.get("w"), Some(1));
+
+        let signals = signals.with_overrides(&[("y", 3)]).unwrap();
+        assert_eq!(signals.get("w"), Some(1));
+
+        let signals = signals.with_overrides(&[("z", 2), ("x", 0)]).unwrap();
+        assert_eq!(signals.get("x"), Some(0));
+        assert_eq!(signals.get("y"), Some(3));
+        assert_eq!(signals.get("w"), Some(2));
+
+        assert_eq!(plan.signals().get("w"), Some(1));
+    }
+
+    #[test]
+    fn override_matches_full_emulation() {
+        let chain = |middle: &str| -> Circuit {
+            let instructions = iter::once("1 -> a".parse().unwrap())
+                .chain((1..1000).map(|idx| {
+                    if idx == 500 {
+                        format!("{} -> {}", middle, wire_name(idx))
+                    } else {
+                        format!("NOT {} -> {}", wire_name(idx - 1), wire_name(idx))
+                    }
+                    .parse()
+                    .unwrap()
+                }))
+                .collect();
+
+            Circuit { instructions }
+        };
+
+        let circuit = chain(&wire_name(499));
+        let plan = circuit.compile().unwrap();
+        let middle = wire_name(500);
+
+        let overridden = plan.signals().with_overrides(&[(&middle, 7)]).unwrap();
+
+        assert_eq!(overridden.to_map(), chain("7").emulate().unwrap());
+        assert_eq!(overridden.get(&wire_name(999)), Some(!7));
+    }
+
+    #[test]
+    fn apply_extended_gates() {
+        let apply = |gate: Gate, x, y| gate.apply_to(x, y, 8);
+
+        assert_eq!(apply(Gate::Xor, 0b1100, 0b1010), 0b0110);
+        assert_eq!(apply(Gate::Nand, 0b1100, 0b1010), 0b1111_0111);
+        assert_eq!(apply(Gate::Nor, 0b1100, 0b1010), 0b1111_0001);
+        assert_eq!(apply(Gate::Xnor, 0b1100, 0b1010), 0b1111_1001);
+        assert_eq!(apply(Gate::LRotate, 0b1000_0001, 1), 0b0000_0011);
+        assert_eq!(apply(Gate::RRotate, 0b1000_0001, 1), 0b1100_0000);
+        assert_eq!(apply(Gate::LRotate, 0b1000_0001, 17), 0b0000_0011);
+        assert_eq!(apply(Gate::Add, 200, 100), 44);
+        assert_eq!(apply(Gate::Sub, 1, 2), 255);
+        assert_eq!(apply(Gate::Mul, 16, 17), 16);
+    }
+
+    #[test]
+    fn shift_by_bus_width_or_more() {
+        for &bits in &[1, 8, 16, 64] {
+            for &shift in &[u64::from(bits), 64, 1000, u64::MAX] {
+                assert_eq!(Gate::LShift.apply_to(1, shift, bits), 0);
+                assert_eq!(Gate::RShift.apply_to(1, shift, bits), 0);
+            }
+        }
+
+        assert_eq!(Gate::LShift.apply_to(1, 63, 64), 1 << 63);
+        assert_eq!(Gate::LRotate.apply_to(1 << 63, 64, 64), 1 << 63);
+    }
+
+    #[test]
+    fn emulate_with_other_bus_widths() {
+        let circuit = Circuit {
+            instructions: vec![
+                "200 -> x".parse().unwrap(),
+                "x ADD 100 -> y".parse().unwrap(),
+                "NOT x -> z".parse().unwrap(),
+            ],
+        };
+
+        let signals = circuit.compile_as::<u8>().unwrap().emulate();
+        assert_eq!((signals["y"], signals["z"]), (44, 55));
+
+        let signals = circuit.compile_as::<u64>().unwrap().emulate();
+        assert_eq!((signals["y"], signals["z"]), (300, !200));
+
+        let signals = circuit.compile_as::<Bits<9>>().unwrap().emulate();
+        assert_eq!(signals["y"], Bits::new(300).unwrap());
+        assert_eq!(signals["z"], Bits::new(511 - 200).unwrap());
+    }
+
+    #[test]
+    fn report_literal_too_wide_for_bus() {
+        let circuit = Circuit {
+            instructions: vec![
+                "256 -> x".parse().unwrap(),
+                "x AND 256 -> y".parse().unwrap(),
+                "y OR 300 -> z".parse().unwrap(),
+            ],
+        };
+
+        assert_eq!(
+            circuit.compile_as::<u8>().unwrap_err(),
+            [
+                CircuitError::LiteralTooWide {
+                    literal: 256,
+                    bits: 8
+                },
+                CircuitError::LiteralTooWide {
+                    literal: 300,
+                    bits: 8
+                },
+            ],
+        );
+        assert!(circuit.compile().is_ok());
+    }
+
+    #[test]
+    fn report_override_of_missing_wire() {
+        let circuit = Circuit {
+            instructions: vec!["1 -> a".parse().unwrap()],
+        };
+
+        assert_eq!(
+            circuit.emulate_with_overrides(&[("b", 1)]),
+            Err(vec![CircuitError::UndefinedWire("b".to_string())]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::*;
+
+    #[test]
+    fn accept_valid_circuit() {
+        assert_eq!(
+            circuit(&["x AND y -> z", "1 -> x", "2 -> y"]).validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn report_undefined_wire_once() {
+        assert_eq!(
+            circuit(&["x AND y -> z", "x OR z -> w", "1 -> y"]).emulate(),
+            Err(vec![CircuitError::UndefinedWire("x".to_string())]),
+        );
+    }
+
+    #[test]
+    fn report_wire_driven_twice() {
+        assert_eq!(
+            circuit(&["1 -> x", "2 -> x"]).validate(),
+            Err(vec![CircuitError::DrivenTwice("x".to_string())]),
+        );
+    }
+
+    #[test]
+    fn report_wires_in_cycle() {
+        assert_eq!(
+            circuit(&["1 -> c", "c AND a -> b", "b -> a", "a -> d"]).emulate(),
+            Err(vec![CircuitError::Cycle(vec![
+                "b".to_string(),
+                "a".to_string(),
+            ])]),
+        );
+    }
+
+    #[test]
+    fn report_wire_that_depends_on_itself() {
+        assert_eq!(
+            circuit(&["NOT a -> a"]).validate(),
+            Err(vec![CircuitError::Cycle(vec!["a".to_string()])]),
+        );
+    }
+
+    #[test]
+    fn report_each_cycle_separately() {
+        let errors = circuit(&["a -> b", "b -> a", "c -> d", "d -> c"])
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn handle_long_chains_without_recursing() {
+        let mut instructions: Vec<_> = (0..100_000)
+            .map(|idx| format!("{} -> {}", wire_name(idx + 1), wire_name(idx)))
+            .collect();
+
+        instructions.push(format!("{} -> {}", wire_name(0), wire_name(100_000)));
+
+        let instructions: Vec<_> = instructions.iter().map(String::as_str).collect();
+        let errors = circuit(&instructions).validate().unwrap_err();
+
+        assert!(matches!(&errors[..], [CircuitError::Cycle(wires)] if wires.len() == 100_001));
+    }
 }
 
 #[cfg(test)]
@@ -326,4 +802,73 @@ mod parsing_tests {
     fn parse_or_gate() {
         assert_eq!(Gate::new("OR"), Ok(("", Gate::Or)));
     }
+
+    #[test]
+    fn reject_literal_that_overflows() {
+        let error = "18446744073709551616 -> a"
+            .parse::<Instruction>()
+            .unwrap_err();
+
+        assert_eq!(error.column(), 1);
+        assert!(error
+            .expected()
+            .any(|expected| expected == "a number that fits in u64"));
+    }
+
+    #[test]
+    fn parse_extended_gates() {
+        assert_eq!(Gate::new("XNOR"), Ok(("", Gate::Xnor)));
+        assert_eq!(Gate::new("NAND"), Ok(("", Gate::Nand)));
+        assert_eq!(Gate::new("RROTATE"), Ok(("", Gate::RRotate)));
+        assert_eq!(Gate::new("SUB"), Ok(("", Gate::Sub)));
+    }
+
+    #[test]
+    fn report_every_gate_tried() {
+        let error = Gate::new("DIV").unwrap_err();
+
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            [
+                r#""AND""#,
+                r#""OR""#,
+                r#""XOR""#,
+                r#""NAND""#,
+                r#""NOR""#,
+                r#""XNOR""#,
+                r#""LSHIFT""#,
+                r#""RSHIFT""#,
+                r#""LROTATE""#,
+                r#""RROTATE""#,
+                r#""ADD""#,
+                r#""SUB""#,
+                r#""MUL""#,
+            ],
+        );
+    }
+
+    #[test]
+    fn report_error_from_alternative_that_got_furthest() {
+        let error = "x ANDD y -> z".parse::<Instruction>().unwrap_err();
+
+        assert_eq!(error.column(), 2);
+        assert_eq!(error.expected().collect::<Vec<_>>(), [r#"" -> ""#]);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for instruction in &[
+            "123 -> x",
+            "x AND y -> d",
+            "NOT 1 -> h",
+            "y RSHIFT 2 -> g",
+            "x XNOR 7 -> i",
+            "a LROTATE b -> c",
+        ] {
+            assert_eq!(
+                instruction.parse::<Instruction>().unwrap().to_string(),
+                *instruction,
+            );
+        }
+    }
 }
@@ -0,0 +1,224 @@
+use super::netlist::Netlist;
+use super::{Circuit, CircuitError};
+use std::{fmt, iter};
+
+#[derive(Debug, PartialEq)]
+pub struct Analysis<'a> {
+    pub wires: Vec<WireAnalysis<'a>>,
+    /// Each path starts at a primary input.
+    pub critical_paths: Vec<(&'a str, Vec<&'a str>)>,
+    pub dead_wires: Vec<&'a str>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WireAnalysis<'a> {
+    pub wire: &'a str,
+    pub fan_in: usize,
+    pub fan_out: usize,
+    /// Counted from the furthest primary input, a wire driven by a literal alone.
+    pub depth: usize,
+}
+
+impl Circuit {
+    pub fn analyze(&self, outputs: &[&str]) -> Result<Analysis<'_>, Vec<CircuitError>> {
+        let netlist = Netlist::new(self);
+        let order = netlist.validate()?;
+
+        let outputs = outputs
+            .iter()
+            .map(|&output| {
+                netlist
+                    .indices
+                    .get_key_value(output)
+                    .map(|(&output, &idx)| (output, idx))
+                    .ok_or_else(|| vec![CircuitError::UndefinedWire(output.to_string())])
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut fan_out = vec![0; netlist.wires.len()];
+        let mut depths = vec![0; netlist.wires.len()];
+
+        for inputs in &netlist.inputs {
+            for &input in inputs {
+                fan_out[input] += 1;
+            }
+        }
+
+        for &idx in &order {
+            if let Some(depth) = netlist.inputs[idx].iter().map(|&input| depths[input]).max() {
+                depths[idx] = depth + 1;
+            }
+        }
+
+        let critical_paths = outputs
+            .iter()
+            .map(|&(output, idx)| {
+                let mut path = vec![idx];
+
+                // Reversed so ties go to the first operand.
+                while let Some(&deepest) = netlist.inputs[*path.last().unwrap()]
+                    .iter()
+                    .rev()
+                    .max_by_key(|&&input| depths[input])
+                {
+                    path.push(deepest);
+                }
+
+                let path = path.into_iter().rev().map(|idx| netlist.wires[idx]);
+                (output, path.collect())
+            })
+            .collect();
+
+        let mut is_live = vec![false; netlist.wires.len()];
+        let mut to_visit: Vec<_> = outputs.iter().map(|&(_, idx)| idx).collect();
+
+        while let Some(idx) = to_visit.pop() {
+            if !is_live[idx] {
+                is_live[idx] = true;
+                to_visit.extend(&netlist.inputs[idx]);
+            }
+        }
+
+        let wires = (0..netlist.wires.len())
+            .map(|idx| WireAnalysis {
+                wire: netlist.wires[idx],
+                fan_in: netlist.inputs[idx].len(),
+                fan_out: fan_out[idx],
+                depth: depths[idx],
+            })
+            .collect();
+
+        let dead_wires = (0..netlist.wires.len())
+            .filter(|&idx| !is_live[idx])
+            .map(|idx| netlist.wires[idx])
+            .collect();
+
+        Ok(Analysis {
+            wires,
+            critical_paths,
+            dead_wires,
+        })
+    }
+}
+
+impl fmt::Display for Analysis<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .wires
+            .iter()
+            .map(|wire| wire.wire.len())
+            .chain(iter::once("wire".len()))
+            .max()
+            .unwrap();
+
+        writeln!(
+            f,
+            "{:<width$}  fan-in  fan-out  depth",
+            "wire",
+            width = width
+        )?;
+
+        for wire in &self.wires {
+            writeln!(
+                f,
+                "{:<width$}  {:>6}  {:>7}  {:>5}",
+                wire.wire,
+                wire.fan_in,
+                wire.fan_out,
+                wire.depth,
+                width = width,
+            )?;
+        }
+
+        for (output, path) in &self.critical_paths {
+            writeln!(
+                f,
+                "\ncritical path to {} (depth {}): {}",
+                output,
+                path.len() - 1,
+                path.join(" -> "),
+            )?;
+        }
+
+        if self.dead_wires.is_empty() {
+            writeln!(f, "\nno dead wires")
+        } else {
+            writeln!(f, "\ndead wires: {}", self.dead_wires.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use super::*;
+    use crate::circuit::circuit;
+
+    #[test]
+    fn analyze_wires() {
+        let circuit = circuit(&[
+            "1 -> x",
+            "2 -> y",
+            "x AND y -> z",
+            "NOT z -> w",
+            "w OR x -> a",
+            "y -> unused",
+        ]);
+
+        let analysis = circuit.analyze(&["a"]).unwrap();
+
+        assert_eq!(
+            analysis.wires[..3],
+            [
+                WireAnalysis {
+                    wire: "x",
+                    fan_in: 0,
+                    fan_out: 2,
+                    depth: 0,
+                },
+                WireAnalysis {
+                    wire: "y",
+                    fan_in: 0,
+                    fan_out: 2,
+                    depth: 0,
+                },
+                WireAnalysis {
+                    wire: "z",
+                    fan_in: 2,
+                    fan_out: 1,
+                    depth: 1,
+                },
+            ],
+        );
+        assert_eq!(analysis.wires[4].depth, 3);
+        assert_eq!(analysis.critical_paths, [("a", vec!["x", "z", "w", "a"])]);
+        assert_eq!(analysis.dead_wires, ["unused"]);
+    }
+
+    #[test]
+    fn display_analysis() {
+        let circuit = circuit(&["1 -> x", "x -> a", "2 -> b"]);
+        let analysis = circuit.analyze(&["a"]).unwrap();
+
+        assert_eq!(
+            analysis.to_string(),
+            "\
+wire  fan-in  fan-out  depth
+x          0        1      0
+a          1        0      1
+b          0        0      0
+
+critical path to a (depth 1): x -> a
+
+dead wires: b
+",
+        );
+    }
+
+    #[test]
+    fn report_missing_output() {
+        assert_eq!(
+            circuit(&["1 -> x"]).analyze(&["a"]).unwrap_err(),
+            [CircuitError::UndefinedWire("a".to_string())],
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use super::Gate;
+use std::collections::HashMap;
+
+pub(super) type Bdd = usize;
+
+pub(super) const FALSE: Bdd = 0;
+pub(super) const TRUE: Bdd = 1;
+
+#[derive(Debug, PartialEq)]
+pub(super) struct TooManyNodes;
+
+/// Reduced and ordered, so equal functions get equal nodes. Lower-numbered
+/// variables are tested first.
+pub(super) struct Manager {
+    nodes: Vec<Node>,
+    unique: HashMap<Node, Bdd>,
+    ite_cache: HashMap<(Bdd, Bdd, Bdd), Bdd>,
+    max_nodes: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Node {
+    var: u32,
+    low: Bdd,
+    high: Bdd,
+}
+
+impl Manager {
+    pub(super) fn new(max_nodes: usize) -> Self {
+        let terminal = Node {
+            var: u32::MAX,
+            low: FALSE,
+            high: FALSE,
+        };
+
+        Self {
+            nodes: vec![terminal, terminal],
+            unique: HashMap::new(),
+            ite_cache: HashMap::new(),
+            max_nodes,
+        }
+    }
+
+    pub(super) fn var(&mut self, var: u32) -> Result<Bdd, TooManyNodes> {
+        self.make(var, FALSE, TRUE)
+    }
+
+    fn make(&mut self, var: u32, low: Bdd, high: Bdd) -> Result<Bdd, TooManyNodes> {
+        if low == high {
+            return Ok(low);
+        }
+
+        let node = Node { var, low, high };
+
+        if let Some(&bdd) = self.unique.get(&node) {
+            return Ok(bdd);
+        }
+
+        if self.nodes.len() >= self.max_nodes {
+            return Err(TooManyNodes);
+        }
+
+        self.nodes.push(node);
+        self.unique.insert(node, self.nodes.len() - 1);
+
+        Ok(self.nodes.len() - 1)
+    }
+
+    pub(super) fn ite(&mut self, f: Bdd, g: Bdd, h: Bdd) -> Result<Bdd, TooManyNodes> {
+        match (f, g, h) {
+            (TRUE, _, _) => return Ok(g),
+            (FALSE, _, _) => return Ok(h),
+            _ if g == h => return Ok(g),
+            (_, TRUE, FALSE) => return Ok(f),
+            _ => {}
+        }
+
+        if let Some(&bdd) = self.ite_cache.get(&(f, g, h)) {
+            return Ok(bdd);
+        }
+
+        let var = self.nodes[f]
+            .var
+            .min(self.nodes[g].var)
+            .min(self.nodes[h].var);
+
+        let (f_low, f_high) = self.cofactors(f, var);
+        let (g_low, g_high) = self.cofactors(g, var);
+        let (h_low, h_high) = self.cofactors(h, var);
+
+        let low = self.ite(f_low, g_low, h_low)?;
+        let high = self.ite(f_high, g_high, h_high)?;
+        let bdd = self.make(var, low, high)?;
+
+        self.ite_cache.insert((f, g, h), bdd);
+        Ok(bdd)
+    }
+
+    fn cofactors(&self, f: Bdd, var: u32) -> (Bdd, Bdd) {
+        let node = self.nodes[f];
+
+        if node.var == var {
+            (node.low, node.high)
+        } else {
+            (f, f)
+        }
+    }
+
+    pub(super) fn not(&mut self, f: Bdd) -> Result<Bdd, TooManyNodes> {
+        self.ite(f, FALSE, TRUE)
+    }
+
+    pub(super) fn and(&mut self, f: Bdd, g: Bdd) -> Result<Bdd, TooManyNodes> {
+        self.ite(f, g, FALSE)
+    }
+
+    pub(super) fn or(&mut self, f: Bdd, g: Bdd) -> Result<Bdd, TooManyNodes> {
+        self.ite(f, TRUE, g)
+    }
+
+    pub(super) fn xor(&mut self, f: Bdd, g: Bdd) -> Result<Bdd, TooManyNodes> {
+        let not_g = self.not(g)?;
+        self.ite(f, not_g, g)
+    }
+
+    /// Gives the variables that must be set; the rest may be anything.
+    pub(super) fn satisfy(&self, mut f: Bdd) -> Option<Vec<u32>> {
+        let mut set = Vec::new();
+
+        while f > TRUE {
+            let node = self.nodes[f];
+
+            if node.low == FALSE {
+                set.push(node.var);
+                f = node.high;
+            } else {
+                f = node.low;
+            }
+        }
+
+        (f == TRUE).then_some(set)
+    }
+
+    /// Words are given bit by bit, least significant first.
+    pub(super) fn apply_gate(
+        &mut self,
+        gate: Gate,
+        x: &[Bdd],
+        y: &[Bdd],
+    ) -> Result<Vec<Bdd>, TooManyNodes> {
+        let bitwise = |manager: &mut Self, op: fn(&mut Self, Bdd, Bdd) -> _, negate: bool| {
+            x.iter()
+                .zip(y)
+                .map(|(&x, &y)| {
+                    let bit = op(manager, x, y)?;
+
+                    if negate {
+                        manager.not(bit)
+                    } else {
+                        Ok(bit)
+                    }
+                })
+                .collect()
+        };
+
+        match gate {
+            Gate::And => bitwise(self, Self::and, false),
+            Gate::Or => bitwise(self, Self::or, false),
+            Gate::Xor => bitwise(self, Self::xor, false),
+            Gate::Nand => bitwise(self, Self::and, true),
+            Gate::Nor => bitwise(self, Self::or, true),
+            Gate::Xnor => bitwise(self, Self::xor, true),
+            Gate::LShift | Gate::RShift | Gate::LRotate | Gate::RRotate => {
+                self.barrel_shift(gate, x, y)
+            }
+            Gate::Add => self.add(x, y, FALSE),
+            Gate::Sub => {
+                let not_y = self.not_word(y)?;
+                self.add(x, &not_y, TRUE)
+            }
+            Gate::Mul => self.mul(x, y),
+        }
+    }
+
+    pub(super) fn not_word(&mut self, x: &[Bdd]) -> Result<Vec<Bdd>, TooManyNodes> {
+        x.iter().map(|&bit| self.not(bit)).collect()
+    }
+
+    fn add(&mut self, x: &[Bdd], y: &[Bdd], mut carry: Bdd) -> Result<Vec<Bdd>, TooManyNodes> {
+        let mut sum = Vec::with_capacity(x.len());
+
+        for (&x, &y) in x.iter().zip(y) {
+            let half_sum = self.xor(x, y)?;
+            sum.push(self.xor(half_sum, carry)?);
+
+            let both = self.and(x, y)?;
+            carry = self.ite(half_sum, carry, both)?;
+        }
+
+        Ok(sum)
+    }
+
+    fn mul(&mut self, x: &[Bdd], y: &[Bdd]) -> Result<Vec<Bdd>, TooManyNodes> {
+        let mut product = vec![FALSE; x.len()];
+
+        for (shift, &y_bit) in y.iter().enumerate() {
+            let mut partial = vec![FALSE; x.len()];
+
+            for idx in shift..x.len() {
+                partial[idx] = self.and(x[idx - shift], y_bit)?;
+            }
+
+            product = self.add(&product, &partial, FALSE)?;
+        }
+
+        Ok(product)
+    }
+
+    fn barrel_shift(&mut self, gate: Gate, x: &[Bdd], y: &[Bdd]) -> Result<Vec<Bdd>, TooManyNodes> {
+        let width = x.len();
+        let mut word = x.to_vec();
+
+        for (power, &y_bit) in y.iter().enumerate() {
+            let distance = 1_usize.checked_shl(power as u32).unwrap_or(usize::MAX);
+
+            let shifted: Vec<_> = (0..width)
+                .map(|idx| match gate {
+                    Gate::LShift => idx.checked_sub(distance).map_or(FALSE, |from| word[from]),
+                    Gate::RShift => idx
+                        .checked_add(distance)
+                        .filter(|&from| from < width)
+                        .map_or(FALSE, |from| word[from]),
+                    Gate::LRotate => word[(idx + width - distance % width) % width],
+                    Gate::RRotate => word[(idx + distance % width) % width],
+                    _ => unreachable!("only shifts and rotations are barrel shifted"),
+                })
+                .collect();
+
+            word = word
+                .iter()
+                .zip(shifted)
+                .map(|(&unshifted, shifted)| self.ite(y_bit, shifted, unshifted))
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(word)
+    }
+
+    #[cfg(test)]
+    pub(super) fn evaluate(&self, mut f: Bdd, assignment: impl Fn(u32) -> bool) -> bool {
+        while f > TRUE {
+            let node = self.nodes[f];
+            f = if assignment(node.var) {
+                node.high
+            } else {
+                node.low
+            };
+        }
+
+        f == TRUE
+    }
+}
+
+#[cfg(test)]
+mod bdd_tests {
+    use super::*;
+
+    #[test]
+    fn equal_functions_share_a_node() {
+        let mut manager = Manager::new(1000);
+        let x = manager.var(0).unwrap();
+        let y = manager.var(1).unwrap();
+
+        let x_and_y = manager.and(x, y).unwrap();
+        let not_x = manager.not(x).unwrap();
+        let not_y = manager.not(y).unwrap();
+        let not_x_or_not_y = manager.or(not_x, not_y).unwrap();
+
+        assert_eq!(manager.not(not_x_or_not_y), Ok(x_and_y));
+    }
+
+    #[test]
+    fn satisfy_function() {
+        let mut manager = Manager::new(1000);
+        let x = manager.var(0).unwrap();
+        let y = manager.var(1).unwrap();
+        let not_x = manager.not(x).unwrap();
+        let f = manager.and(not_x, y).unwrap();
+
+        assert_eq!(manager.satisfy(f), Some(vec![1]));
+        assert_eq!(manager.satisfy(FALSE), None);
+    }
+
+    #[test]
+    fn stop_at_node_limit() {
+        let mut manager = Manager::new(4);
+        let x = manager.var(0).unwrap();
+        let y = manager.var(1).unwrap();
+
+        assert_eq!(manager.and(x, y), Err(TooManyNodes));
+    }
+}
@@ -0,0 +1,218 @@
+use super::{Circuit, CircuitError, Instruction, Signal, SingleSignal};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter;
+
+#[derive(Debug, Clone)]
+pub struct Dot<'c, W = u16> {
+    circuit: &'c Circuit,
+    values: Option<&'c HashMap<&'c str, W>>,
+    cone: Option<HashSet<&'c str>>,
+}
+
+impl<'c> Dot<'c> {
+    pub(super) fn new(circuit: &'c Circuit) -> Self {
+        Self {
+            circuit,
+            values: None,
+            cone: None,
+        }
+    }
+}
+
+impl<'c, W> Dot<'c, W> {
+    pub fn with_values<V>(self, values: &'c HashMap<&'c str, V>) -> Dot<'c, V> {
+        Dot {
+            circuit: self.circuit,
+            values: Some(values),
+            cone: self.cone,
+        }
+    }
+
+    pub fn cone_of(mut self, wire: &str) -> Result<Self, CircuitError> {
+        let mut drivers: HashMap<&str, Vec<&Signal>> = HashMap::new();
+
+        for Instruction { signal, wire } in &self.circuit.instructions {
+            drivers.entry(wire).or_default().push(signal);
+        }
+
+        let (&wire, _) = drivers
+            .get_key_value(wire)
+            .ok_or_else(|| CircuitError::UndefinedWire(wire.to_string()))?;
+
+        let mut cone = HashSet::new();
+        let mut to_visit = vec![wire];
+
+        while let Some(wire) = to_visit.pop() {
+            if !cone.insert(wire) {
+                continue;
+            }
+
+            let signals = drivers.get(wire).into_iter().flatten();
+            to_visit.extend(signals.flat_map(|signal| signal.wires()));
+        }
+
+        self.cone = Some(cone);
+        Ok(self)
+    }
+
+    fn is_shown(&self, wire: &str) -> bool {
+        self.cone.as_ref().is_none_or(|cone| cone.contains(wire))
+    }
+}
+
+// Wire IDs are quoted, since a wire could be named after a DOT keyword.
+impl<W: fmt::Display> fmt::Display for Dot<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instructions = self
+            .circuit
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| self.is_shown(&instruction.wire));
+
+        writeln!(f, "digraph circuit {{")?;
+
+        let mut declared = HashSet::new();
+
+        for (_, Instruction { signal, wire }) in instructions.clone() {
+            let wires = signal.wires().chain(iter::once(wire.as_str()));
+
+            for wire in wires.filter(|&wire| declared.insert(wire)) {
+                match self.values.and_then(|values| values.get(wire)) {
+                    Some(value) => {
+                        writeln!(f, "    \"{}\" [label=\"{}\\n{}\"];", wire, wire, value)?
+                    }
+                    None => writeln!(f, "    \"{}\";", wire)?,
+                }
+            }
+        }
+
+        let mut literals = 0;
+        let mut operand =
+            |f: &mut fmt::Formatter<'_>, single_signal: &SingleSignal| match single_signal {
+                SingleSignal::Wire(wire) => Ok(format!("\"{}\"", wire)),
+                SingleSignal::Literal(n) => {
+                    let id = format!("literal{}", literals);
+                    literals += 1;
+                    writeln!(f, "    {} [shape=plaintext, label=\"{}\"];", id, n)?;
+
+                    Ok(id)
+                }
+            };
+
+        for (idx, Instruction { signal, wire }) in instructions {
+            let gate = format!("gate{}", idx);
+
+            match signal {
+                Signal::WithGate { lhs, rhs, gate: op } => {
+                    writeln!(f, "    {} [shape=box, label=\"{}\"];", gate, op)?;
+
+                    let lhs = operand(f, lhs)?;
+                    let rhs = operand(f, rhs)?;
+
+                    if op.is_commutative() {
+                        writeln!(f, "    {} -> {};", lhs, gate)?;
+                        writeln!(f, "    {} -> {};", rhs, gate)?;
+                    } else {
+                        writeln!(f, "    {} -> {} [label=\"lhs\"];", lhs, gate)?;
+                        writeln!(f, "    {} -> {} [label=\"rhs\"];", rhs, gate)?;
+                    }
+
+                    writeln!(f, "    {} -> \"{}\";", gate, wire)?;
+                }
+                Signal::Not { signal } => {
+                    writeln!(f, "    {} [shape=box, label=\"NOT\"];", gate)?;
+
+                    let input = operand(f, signal)?;
+                    writeln!(f, "    {} -> {};", input, gate)?;
+                    writeln!(f, "    {} -> \"{}\";", gate, wire)?;
+                }
+                Signal::Single(single_signal) => {
+                    let input = operand(f, single_signal)?;
+                    writeln!(f, "    {} -> \"{}\";", input, wire)?;
+                }
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod dot_tests {
+    use super::*;
+    use crate::circuit::circuit;
+
+    #[test]
+    fn render_circuit() {
+        let circuit = circuit(&[
+            "123 -> x",
+            "x LSHIFT 2 -> f",
+            "NOT x -> h",
+            "f AND h -> node",
+        ]);
+
+        assert_eq!(
+            circuit.to_dot().to_string(),
+            r#"digraph circuit {
+    "x";
+    "f";
+    "h";
+    "node";
+    literal0 [shape=plaintext, label="123"];
+    literal0 -> "x";
+    gate1 [shape=box, label="LSHIFT"];
+    literal1 [shape=plaintext, label="2"];
+    "x" -> gate1 [label="lhs"];
+    literal1 -> gate1 [label="rhs"];
+    gate1 -> "f";
+    gate2 [shape=box, label="NOT"];
+    "x" -> gate2;
+    gate2 -> "h";
+    gate3 [shape=box, label="AND"];
+    "f" -> gate3;
+    "h" -> gate3;
+    gate3 -> "node";
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn render_values() {
+        let circuit = circuit(&["3 -> x", "x -> y"]);
+        let values = circuit.emulate().unwrap();
+
+        assert_eq!(
+            circuit.to_dot().with_values(&values).to_string(),
+            r#"digraph circuit {
+    "x" [label="x\n3"];
+    "y" [label="y\n3"];
+    literal0 [shape=plaintext, label="3"];
+    literal0 -> "x";
+    "x" -> "y";
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn render_cone() {
+        let circuit = circuit(&["1 -> x", "2 -> y", "x OR z -> a", "y -> b"]);
+        let dot = circuit.to_dot().cone_of("a").unwrap().to_string();
+
+        assert!(dot.contains(r#""x" -> gate2;"#));
+        assert!(dot.contains(r#""z";"#));
+        assert!(!dot.contains(r#""y""#));
+        assert!(!dot.contains(r#""b""#));
+    }
+
+    #[test]
+    fn report_cone_of_missing_wire() {
+        assert_eq!(
+            circuit(&["1 -> x"]).to_dot().cone_of("a").unwrap_err(),
+            CircuitError::UndefinedWire("a".to_string()),
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use super::bdd::{Manager, TooManyNodes, FALSE};
+use super::symbolic::{Expr, Symbolic};
+use super::word::{self, Word};
+use super::{Circuit, CircuitError};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Beyond this, circuits are compared as binary decision diagrams.
+const MAX_EXHAUSTIVE_BITS: u32 = 16;
+
+const MAX_BDD_NODES: usize = 1 << 22;
+
+#[derive(Debug, PartialEq)]
+pub enum Equivalence<W = u16> {
+    Equivalent,
+    Counterexample(Counterexample<W>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Counterexample<W = u16> {
+    pub inputs: Vec<(String, W)>,
+    pub output: String,
+    pub first: W,
+    pub second: W,
+}
+
+impl<W: Word> fmt::Display for Counterexample<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inputs: Vec<_> = self
+            .inputs
+            .iter()
+            .map(|(wire, value)| format!("{} = {}", wire, value))
+            .collect();
+
+        write!(
+            f,
+            "with {}, wire {} is {} in the first circuit but {} in the second",
+            inputs.join(", "),
+            self.output,
+            self.first,
+            self.second,
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EquivalenceError {
+    First(Vec<CircuitError>),
+    Second(Vec<CircuitError>),
+    TooComplex,
+}
+
+impl fmt::Display for EquivalenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (circuit, errors) = match self {
+            Self::First(errors) => ("first", errors),
+            Self::Second(errors) => ("second", errors),
+            Self::TooComplex => return write!(f, "the circuits are too complex to compare"),
+        };
+
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        write!(f, "in the {} circuit, {}", circuit, errors.join("; "))
+    }
+}
+
+impl std::error::Error for EquivalenceError {}
+
+impl From<TooManyNodes> for EquivalenceError {
+    fn from(_: TooManyNodes) -> Self {
+        Self::TooComplex
+    }
+}
+
+impl Circuit {
+    pub fn check_equivalence(
+        &self,
+        other: &Circuit,
+        inputs: &[&str],
+        outputs: &[&str],
+    ) -> Result<Equivalence, EquivalenceError> {
+        self.check_equivalence_as(other, inputs, outputs)
+    }
+
+    /// Whatever normally drives `inputs` is ignored.
+    pub fn check_equivalence_as<W: Word>(
+        &self,
+        other: &Circuit,
+        inputs: &[&str],
+        outputs: &[&str],
+    ) -> Result<Equivalence<W>, EquivalenceError> {
+        let first = self
+            .symbolic_as::<W>(inputs)
+            .map_err(EquivalenceError::First)?;
+        let second = other
+            .symbolic_as::<W>(inputs)
+            .map_err(EquivalenceError::Second)?;
+
+        let first_outputs = output_exprs(&first, outputs).map_err(EquivalenceError::First)?;
+        let second_outputs = output_exprs(&second, outputs).map_err(EquivalenceError::Second)?;
+        let pairs: Vec<_> = first_outputs.into_iter().zip(second_outputs).collect();
+
+        let mut is_used = vec![false; inputs.len()];
+
+        for expr in pairs.iter().flat_map(|(first, second)| [first, second]) {
+            for input in expr.inputs() {
+                is_used[inputs.iter().position(|&wire| wire == input).unwrap()] = true;
+            }
+        }
+
+        let used: Vec<_> = (0..inputs.len()).filter(|&idx| is_used[idx]).collect();
+
+        let values = if used.len() as u32 * W::BITS <= MAX_EXHAUSTIVE_BITS {
+            find_difference_exhaustively(&pairs, inputs.len(), &used)
+        } else {
+            find_difference_with_bdds(&pairs, inputs.len())?
+        };
+
+        let values = match values {
+            Some(values) => values,
+            None => return Ok(Equivalence::Equivalent),
+        };
+
+        let (output, first, second) = outputs
+            .iter()
+            .zip(&pairs)
+            .map(|(output, (first, second))| {
+                (output, first.evaluate(&values), second.evaluate(&values))
+            })
+            .find(|(_, first, second)| first != second)
+            .expect("a difference was found");
+
+        Ok(Equivalence::Counterexample(Counterexample {
+            inputs: inputs
+                .iter()
+                .map(|input| input.to_string())
+                .zip(values)
+                .collect(),
+            output: output.to_string(),
+            first,
+            second,
+        }))
+    }
+}
+
+type ExprPair<'s, 'a, W> = (Expr<'s, 'a, W>, Expr<'s, 'a, W>);
+
+fn output_exprs<'s, 'a, W: Word>(
+    symbolic: &'s Symbolic<'a, W>,
+    outputs: &[&str],
+) -> Result<Vec<Expr<'s, 'a, W>>, Vec<CircuitError>> {
+    outputs
+        .iter()
+        .map(|&output| {
+            symbolic
+                .wire(output)
+                .ok_or_else(|| vec![CircuitError::UndefinedWire(output.to_string())])
+        })
+        .collect()
+}
+
+fn find_difference_exhaustively<W: Word>(
+    pairs: &[ExprPair<'_, '_, W>],
+    num_inputs: usize,
+    used: &[usize],
+) -> Option<Vec<W>> {
+    let mask = word::mask(W::BITS);
+    let mut values = vec![W::from_bits(0); num_inputs];
+
+    for assignment in 0..1_u64 << (used.len() as u32 * W::BITS) {
+        for (position, &idx) in used.iter().enumerate() {
+            values[idx] = W::from_bits(assignment >> (position as u32 * W::BITS) & mask);
+        }
+
+        if pairs
+            .iter()
+            .any(|(first, second)| first.evaluate(&values) != second.evaluate(&values))
+        {
+            return Some(values);
+        }
+    }
+
+    None
+}
+
+fn find_difference_with_bdds<W: Word>(
+    pairs: &[ExprPair<'_, '_, W>],
+    num_inputs: usize,
+) -> Result<Option<Vec<W>>, TooManyNodes> {
+    // Interleaving the bits of the inputs keeps the diagrams for arithmetic
+    // small.
+    let var = |input: usize, bit: u32| bit * num_inputs as u32 + input as u32;
+
+    let mut manager = Manager::new(MAX_BDD_NODES);
+    let mut first_blasted = HashMap::new();
+    let mut second_blasted = HashMap::new();
+
+    for (first, second) in pairs {
+        let first = first.bit_blast(&mut manager, &mut first_blasted, var)?;
+        let second = second.bit_blast(&mut manager, &mut second_blasted, var)?;
+
+        for (first, second) in first.into_iter().zip(second) {
+            let difference = manager.xor(first, second)?;
+
+            if difference == FALSE {
+                continue;
+            }
+
+            let mut values = vec![0; num_inputs];
+
+            for var in manager
+                .satisfy(difference)
+                .expect("differences are satisfiable")
+            {
+                values[var as usize % num_inputs] |= 1 << (var / num_inputs as u32);
+            }
+
+            return Ok(Some(values.into_iter().map(W::from_bits).collect()));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod equivalence_tests {
+    use super::*;
+    use crate::circuit::circuit;
+    use crate::parsing::parse_lines;
+
+    #[test]
+    fn prove_small_circuits_equivalent() {
+        let first = circuit(&["x AND y -> z", "0 -> x", "0 -> y"]);
+        let second = circuit(&[
+            "NOT x -> p",
+            "NOT y -> q",
+            "p OR q -> r",
+            "NOT r -> z",
+            "1 -> x",
+            "1 -> y",
+        ]);
+
+        assert_eq!(
+            first.check_equivalence_as::<u8>(&second, &["x", "y"], &["z"]),
+            Ok(Equivalence::Equivalent),
+        );
+    }
+
+    #[test]
+    fn find_counterexample_exhaustively() {
+        let first = circuit(&["x AND 7 -> z", "0 -> x"]);
+        let second = circuit(&["x AND 15 -> z", "0 -> x"]);
+
+        assert_eq!(
+            first.check_equivalence(&second, &["x"], &["z"]),
+            Ok(Equivalence::Counterexample(Counterexample {
+                inputs: vec![("x".to_string(), 8)],
+                output: "z".to_string(),
+                first: 0,
+                second: 8,
+            })),
+        );
+    }
+
+    #[test]
+    fn prove_wide_circuits_equivalent_with_bdds() {
+        let first = circuit(&["x SUB y -> z", "0 -> x", "0 -> y", "x ADD y -> w"]);
+        let second = circuit(&[
+            "NOT y -> n",
+            "x ADD n -> s",
+            "s ADD 1 -> z",
+            "0 -> x",
+            "0 -> y",
+            "y ADD x -> w",
+        ]);
+
+        assert_eq!(
+            first.check_equivalence_as::<u32>(&second, &["x", "y"], &["z", "w"]),
+            Ok(Equivalence::Equivalent),
+        );
+    }
+
+    #[test]
+    fn find_counterexample_with_bdds() {
+        let first = circuit(&["x OR y -> z", "0 -> x", "0 -> y"]);
+        let second = circuit(&["x XOR y -> z", "0 -> x", "0 -> y"]);
+
+        let counterexample = match first.check_equivalence(&second, &["x", "y"], &["z"]) {
+            Ok(Equivalence::Counterexample(counterexample)) => counterexample,
+            result => panic!("expected a counterexample, got {:?}", result),
+        };
+
+        let (x, y) = (counterexample.inputs[0].1, counterexample.inputs[1].1);
+        assert_ne!(x & y, 0);
+        assert_eq!(counterexample.first, x | y);
+        assert_eq!(counterexample.second, x ^ y);
+    }
+
+    #[test]
+    fn bit_blast_every_gate() {
+        for gate in &[
+            "AND", "OR", "XOR", "NAND", "NOR", "XNOR", "LSHIFT", "RSHIFT", "LROTATE", "RROTATE",
+            "ADD", "SUB", "MUL",
+        ] {
+            let circuit = circuit(&[&format!("x {} y -> z", gate), "0 -> x", "0 -> y"]);
+            check_bit_blasting::<u8>(&circuit);
+            check_bit_blasting::<crate::circuit::Bits<5>>(&circuit);
+        }
+    }
+
+    fn check_bit_blasting<W: Word>(circuit: &Circuit) {
+        let symbolic = circuit.symbolic_as::<W>(&["x", "y"]).unwrap();
+        let z = symbolic.wire("z").unwrap();
+
+        let mut manager = Manager::new(MAX_BDD_NODES);
+        let var = |input: usize, bit: u32| bit * 2 + input as u32;
+        let bits = z.bit_blast(&mut manager, &mut HashMap::new(), var).unwrap();
+
+        for x in 0..1 << W::BITS {
+            for y in 0..1 << W::BITS {
+                let inputs = [x, y];
+                let expected = z.evaluate(&[W::from_bits(x), W::from_bits(y)]).to_bits();
+
+                let actual = bits.iter().enumerate().fold(0, |value, (bit, &f)| {
+                    let is_set =
+                        manager.evaluate(f, |var| inputs[var as usize % 2] >> (var / 2) & 1 == 1);
+
+                    value | (is_set as u64) << bit
+                });
+
+                assert_eq!(actual, expected, "{} with x = {}, y = {}", circuit, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn prove_simplified_puzzle_input_equivalent() {
+        let circuit = Circuit {
+            instructions: parse_lines(include_str!("../bin/circuit_input"), str::parse)
+                .collect::<Result<_, _>>()
+                .unwrap(),
+        };
+
+        let (simplified, _) = circuit.simplify(&["b", "c"], &["a"]).unwrap();
+
+        assert_eq!(
+            circuit.check_equivalence(&simplified, &["b", "c"], &["a"]),
+            Ok(Equivalence::Equivalent),
+        );
+    }
+
+    #[test]
+    fn report_output_missing_from_second_circuit() {
+        let first = circuit(&["1 -> a"]);
+        let second = circuit(&["1 -> b"]);
+
+        assert_eq!(
+            first.check_equivalence(&second, &[], &["a"]),
+            Err(EquivalenceError::Second(vec![CircuitError::UndefinedWire(
+                "a".to_string()
+            )])),
+        );
+    }
+}
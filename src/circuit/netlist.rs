@@ -0,0 +1,216 @@
+use super::{Circuit, CircuitError, Instruction, Signal};
+use std::collections::HashMap;
+
+/// Wires are indexed in the order they are first driven, so that anything
+/// derived from the netlist comes out in a predictable order.
+pub(super) struct Netlist<'a> {
+    pub(super) indices: HashMap<&'a str, usize>,
+    pub(super) wires: Vec<&'a str>,
+    pub(super) drivers: Vec<&'a Signal>,
+    num_drivers: Vec<usize>,
+    pub(super) inputs: Vec<Vec<usize>>,
+    undefined_wires: Vec<&'a str>,
+}
+
+impl<'a> Netlist<'a> {
+    pub(super) fn new(circuit: &'a Circuit) -> Self {
+        let mut indices = HashMap::with_capacity(circuit.instructions.len());
+        let mut wires = Vec::with_capacity(circuit.instructions.len());
+        let mut drivers = Vec::with_capacity(circuit.instructions.len());
+        let mut num_drivers = Vec::with_capacity(circuit.instructions.len());
+
+        let instruction_wires: Vec<_> = circuit
+            .instructions
+            .iter()
+            .map(|Instruction { wire, signal }| {
+                let idx = *indices.entry(wire.as_str()).or_insert_with(|| {
+                    wires.push(wire.as_str());
+                    drivers.push(signal);
+                    num_drivers.push(0);
+                    wires.len() - 1
+                });
+
+                num_drivers[idx] += 1;
+                idx
+            })
+            .collect();
+
+        let mut inputs = vec![Vec::new(); wires.len()];
+        let mut undefined_wires = Vec::new();
+
+        for (Instruction { signal, .. }, wire) in circuit.instructions.iter().zip(instruction_wires)
+        {
+            for input in signal.wires() {
+                match indices.get(input) {
+                    Some(input_idx) => inputs[wire].push(*input_idx),
+                    None if !undefined_wires.contains(&input) => undefined_wires.push(input),
+                    None => {}
+                }
+            }
+        }
+
+        Self {
+            indices,
+            wires,
+            drivers,
+            num_drivers,
+            inputs,
+            undefined_wires,
+        }
+    }
+
+    pub(super) fn validate(&self) -> Result<Vec<usize>, Vec<CircuitError>> {
+        let mut errors = self.connection_errors();
+        let order = self.topological_order();
+
+        // Finding the cycles is only worth doing once we know there are some.
+        if order.len() < self.wires.len() {
+            errors.extend(self.cycles());
+        }
+
+        if errors.is_empty() {
+            Ok(order)
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub(super) fn connection_errors(&self) -> Vec<CircuitError> {
+        let mut errors: Vec<_> = self
+            .undefined_wires
+            .iter()
+            .map(|wire| CircuitError::UndefinedWire(wire.to_string()))
+            .collect();
+
+        errors.extend(
+            self.wires
+                .iter()
+                .zip(&self.num_drivers)
+                .filter(|(_, num_drivers)| **num_drivers > 1)
+                .map(|(wire, _)| CircuitError::DrivenTwice(wire.to_string())),
+        );
+
+        errors
+    }
+
+    fn cycles(&self) -> impl Iterator<Item = CircuitError> + '_ {
+        strongly_connected_components(&self.inputs)
+            .into_iter()
+            .filter(move |component| {
+                component.len() > 1 || self.inputs[component[0]].contains(&component[0])
+            })
+            .map(move |mut component| {
+                component.sort_unstable();
+
+                CircuitError::Cycle(
+                    component
+                        .iter()
+                        .map(|idx| self.wires[*idx].to_string())
+                        .collect(),
+                )
+            })
+    }
+
+    /// Wires in or downstream of a cycle are left out.
+    fn topological_order(&self) -> Vec<usize> {
+        let mut num_unordered_inputs: Vec<_> = self.inputs.iter().map(Vec::len).collect();
+        let mut readers = vec![Vec::new(); self.wires.len()];
+
+        for (wire, inputs) in self.inputs.iter().enumerate() {
+            for input in inputs {
+                readers[*input].push(wire);
+            }
+        }
+
+        let mut order: Vec<_> = (0..self.wires.len())
+            .filter(|wire| num_unordered_inputs[*wire] == 0)
+            .collect();
+
+        let mut next = 0;
+
+        while let Some(&wire) = order.get(next) {
+            next += 1;
+
+            for reader in &readers[wire] {
+                num_unordered_inputs[*reader] -= 1;
+
+                if num_unordered_inputs[*reader] == 0 {
+                    order.push(*reader);
+                }
+            }
+        }
+
+        order
+    }
+}
+
+/// Tarjan's algorithm, without recursion so that deep circuits cannot
+/// overflow the stack.
+fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; successors.len()];
+    let mut low_link = vec![0; successors.len()];
+    let mut on_stack = vec![false; successors.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..successors.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        // Each frame holds a node and how many of its successors we have
+        // looked at so far.
+        let mut frames = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&(node, num_visited)) = frames.last() {
+            if let Some(&successor) = successors[node].get(num_visited) {
+                frames.last_mut().unwrap().1 += 1;
+
+                if index[successor] == UNVISITED {
+                    index[successor] = next_index;
+                    low_link[successor] = next_index;
+                    next_index += 1;
+                    stack.push(successor);
+                    on_stack[successor] = true;
+                    frames.push((successor, 0));
+                } else if on_stack[successor] {
+                    low_link[node] = low_link[node].min(index[successor]);
+                }
+
+                continue;
+            }
+
+            frames.pop();
+
+            if let Some(&(parent, _)) = frames.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+
+            if low_link[node] == index[node] {
+                let mut component = Vec::new();
+
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+
+                    if member == node {
+                        break;
+                    }
+                }
+
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
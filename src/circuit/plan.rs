@@ -0,0 +1,215 @@
+use super::netlist::Netlist;
+use super::word::{self, Word};
+use super::{CircuitError, Gate, Signal, SingleSignal};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::marker::PhantomData;
+
+/// Each step only reads wires computed by earlier steps.
+#[derive(Debug)]
+pub struct Plan<'a, W = u16> {
+    indices: HashMap<&'a str, usize>,
+    wires: Vec<&'a str>,
+    steps: Vec<Step>,
+    /// The position in `steps` of the step that computes each wire.
+    positions: Vec<usize>,
+    readers: Vec<Vec<usize>>,
+    word: PhantomData<W>,
+}
+
+impl<'a, W: Word> Plan<'a, W> {
+    pub(super) fn new(netlist: Netlist<'a>, order: Vec<usize>) -> Self {
+        let mut positions = vec![0; netlist.wires.len()];
+        let mut readers = vec![Vec::new(); netlist.wires.len()];
+
+        for (position, &wire) in order.iter().enumerate() {
+            positions[wire] = position;
+        }
+
+        for (reader, inputs) in netlist.inputs.iter().enumerate() {
+            for &input in inputs {
+                readers[input].push(reader);
+            }
+        }
+
+        let steps = order
+            .into_iter()
+            .map(|wire| Step {
+                wire,
+                signal: CompiledSignal::new(netlist.drivers[wire], &netlist.indices),
+            })
+            .collect();
+
+        Self {
+            indices: netlist.indices,
+            wires: netlist.wires,
+            steps,
+            positions,
+            readers,
+            word: PhantomData,
+        }
+    }
+
+    pub fn emulate(&self) -> HashMap<&'a str, W> {
+        self.signals().to_map()
+    }
+
+    pub fn signals(&self) -> Signals<'_, 'a, W> {
+        let mut values = vec![W::from_bits(0); self.wires.len()];
+
+        for Step { wire, signal } in &self.steps {
+            values[*wire] = signal.evaluate(&values);
+        }
+
+        Signals {
+            plan: self,
+            values,
+            is_overridden: vec![false; self.wires.len()],
+        }
+    }
+
+    fn index(&self, wire: &str) -> Result<usize, CircuitError> {
+        self.indices
+            .get(wire)
+            .copied()
+            .ok_or_else(|| CircuitError::UndefinedWire(wire.to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Signals<'p, 'a, W = u16> {
+    plan: &'p Plan<'a, W>,
+    values: Vec<W>,
+    is_overridden: Vec<bool>,
+}
+
+impl<'p, 'a, W: Word> Signals<'p, 'a, W> {
+    pub fn get(&self, wire: &str) -> Option<W> {
+        self.plan.indices.get(wire).map(|&idx| self.values[idx])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, W)> + '_ {
+        self.plan
+            .wires
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+
+    pub fn to_map(&self) -> HashMap<&'a str, W> {
+        self.iter().collect()
+    }
+
+    /// Only recomputes wires downstream of a change. Earlier overrides stay.
+    pub fn with_overrides(&self, overrides: &[(&str, W)]) -> Result<Self, CircuitError> {
+        let plan = self.plan;
+        let mut signals = self.clone();
+        let mut queue = BinaryHeap::new();
+        let mut is_queued = vec![false; plan.steps.len()];
+
+        let mut enqueue_readers = |wire: usize, queue: &mut BinaryHeap<_>| {
+            for &reader in &plan.readers[wire] {
+                let position = plan.positions[reader];
+
+                if !is_queued[position] {
+                    is_queued[position] = true;
+                    queue.push(Reverse(position));
+                }
+            }
+        };
+
+        for &(wire, value) in overrides {
+            let idx = plan.index(wire)?;
+            signals.is_overridden[idx] = true;
+
+            if signals.values[idx] != value {
+                signals.values[idx] = value;
+                enqueue_readers(idx, &mut queue);
+            }
+        }
+
+        // Steps are popped in plan order, so each is recomputed at most once,
+        // after all of its changed inputs.
+        while let Some(Reverse(position)) = queue.pop() {
+            let Step { wire, signal } = &plan.steps[position];
+
+            if signals.is_overridden[*wire] {
+                continue;
+            }
+
+            let value = signal.evaluate(&signals.values);
+
+            if signals.values[*wire] != value {
+                signals.values[*wire] = value;
+                enqueue_readers(*wire, &mut queue);
+            }
+        }
+
+        Ok(signals)
+    }
+}
+
+#[derive(Debug)]
+struct Step {
+    wire: usize,
+    signal: CompiledSignal,
+}
+
+#[derive(Debug)]
+pub(super) enum CompiledSignal {
+    WithGate {
+        lhs: Operand,
+        rhs: Operand,
+        gate: Gate,
+    },
+    Not(Operand),
+    Single(Operand),
+}
+
+impl CompiledSignal {
+    pub(super) fn new(signal: &Signal, indices: &HashMap<&str, usize>) -> Self {
+        match signal {
+            Signal::WithGate { lhs, rhs, gate } => Self::WithGate {
+                lhs: Operand::new(lhs, indices),
+                rhs: Operand::new(rhs, indices),
+                gate: *gate,
+            },
+            Signal::Not { signal } => Self::Not(Operand::new(signal, indices)),
+            Signal::Single(single_signal) => Self::Single(Operand::new(single_signal, indices)),
+        }
+    }
+
+    pub(super) fn evaluate<W: Word>(&self, values: &[W]) -> W {
+        let bits = match self {
+            Self::WithGate { lhs, rhs, gate } => {
+                gate.apply_to(lhs.evaluate(values), rhs.evaluate(values), W::BITS)
+            }
+            Self::Not(operand) => !operand.evaluate(values) & word::mask(W::BITS),
+            Self::Single(operand) => operand.evaluate(values),
+        };
+
+        W::from_bits(bits)
+    }
+}
+
+#[derive(Debug)]
+pub(super) enum Operand {
+    Literal(u64),
+    Wire(usize),
+}
+
+impl Operand {
+    fn new(single_signal: &SingleSignal, indices: &HashMap<&str, usize>) -> Self {
+        match single_signal {
+            SingleSignal::Literal(n) => Self::Literal(*n),
+            SingleSignal::Wire(wire) => Self::Wire(indices[wire.as_str()]),
+        }
+    }
+
+    fn evaluate<W: Word>(&self, values: &[W]) -> u64 {
+        match self {
+            Self::Literal(n) => *n,
+            Self::Wire(wire) => values[*wire].to_bits(),
+        }
+    }
+}
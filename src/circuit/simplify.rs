@@ -0,0 +1,261 @@
+use super::word::{self, Word};
+use super::{Circuit, CircuitError, Instruction, Signal, SingleSignal};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimplifyReport {
+    pub folded: usize,
+    pub instructions_before: usize,
+    pub instructions_after: usize,
+    pub gates_before: usize,
+    pub gates_after: usize,
+}
+
+impl SimplifyReport {
+    pub fn gates_removed(&self) -> usize {
+        self.gates_before - self.gates_after
+    }
+}
+
+impl fmt::Display for SimplifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "removed {} of {} gates ({} folded) and {} of {} instructions",
+            self.gates_removed(),
+            self.gates_before,
+            self.folded,
+            self.instructions_before - self.instructions_after,
+            self.instructions_before,
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Value {
+    Constant(u64),
+    Wire(usize),
+}
+
+impl Circuit {
+    pub fn simplify(
+        &self,
+        inputs: &[&str],
+        outputs: &[&str],
+    ) -> Result<(Circuit, SimplifyReport), Vec<CircuitError>> {
+        self.simplify_as::<u16>(inputs, outputs)
+    }
+
+    /// Each of `inputs` is kept as a wire of its own, so it can still be
+    /// overridden.
+    pub fn simplify_as<W: Word>(
+        &self,
+        inputs: &[&str],
+        outputs: &[&str],
+    ) -> Result<(Circuit, SimplifyReport), Vec<CircuitError>> {
+        let (netlist, order) = self.validate_as::<W>()?;
+
+        let index = |wire: &str| {
+            netlist
+                .indices
+                .get(wire)
+                .copied()
+                .ok_or_else(|| vec![CircuitError::UndefinedWire(wire.to_string())])
+        };
+
+        let mut is_input = vec![false; netlist.wires.len()];
+
+        for &input in inputs {
+            is_input[index(input)?] = true;
+        }
+
+        let outputs = outputs
+            .iter()
+            .map(|&output| index(output))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let single_signal = |value| match value {
+            Value::Constant(n) => SingleSignal::Literal(n),
+            Value::Wire(idx) => SingleSignal::Wire(netlist.wires[idx].to_string()),
+        };
+
+        // The simplified signal driving each wire, and what reading it gives.
+        let mut signals = vec![None; netlist.wires.len()];
+        let mut values = vec![Value::Constant(0); netlist.wires.len()];
+        let mut folded = 0;
+
+        for idx in order {
+            let value_of = |single_signal: &SingleSignal| match single_signal {
+                SingleSignal::Literal(n) => Value::Constant(*n),
+                SingleSignal::Wire(wire) => values[netlist.indices[wire.as_str()]],
+            };
+
+            let (signal, value) = match netlist.drivers[idx] {
+                Signal::WithGate { lhs, rhs, gate } => match (value_of(lhs), value_of(rhs)) {
+                    (Value::Constant(x), Value::Constant(y)) => {
+                        folded += 1;
+                        fold(gate.apply_to(x, y, W::BITS))
+                    }
+                    (lhs, rhs) => {
+                        let signal = Signal::WithGate {
+                            lhs: single_signal(lhs),
+                            rhs: single_signal(rhs),
+                            gate: *gate,
+                        };
+
+                        (signal, Value::Wire(idx))
+                    }
+                },
+                Signal::Not { signal } => match value_of(signal) {
+                    Value::Constant(x) => {
+                        folded += 1;
+                        fold(!x & word::mask(W::BITS))
+                    }
+                    value => {
+                        let signal = single_signal(value);
+                        (Signal::Not { signal }, Value::Wire(idx))
+                    }
+                },
+                Signal::Single(signal) => {
+                    let value = value_of(signal);
+                    (Signal::Single(single_signal(value)), value)
+                }
+            };
+
+            signals[idx] = Some(signal);
+            values[idx] = if is_input[idx] {
+                Value::Wire(idx)
+            } else {
+                value
+            };
+        }
+
+        let signals: Vec<Signal> = signals.into_iter().map(Option::unwrap).collect();
+
+        let mut is_needed = vec![false; netlist.wires.len()];
+        let mut to_visit = outputs;
+
+        while let Some(idx) = to_visit.pop() {
+            if !is_needed[idx] {
+                is_needed[idx] = true;
+                to_visit.extend(signals[idx].wires().map(|wire| netlist.indices[wire]));
+            }
+        }
+
+        let instructions: Vec<_> = self
+            .instructions
+            .iter()
+            .filter_map(|instruction| {
+                let idx = netlist.indices[instruction.wire.as_str()];
+
+                is_needed[idx].then(|| Instruction {
+                    signal: signals[idx].clone(),
+                    wire: instruction.wire.clone(),
+                })
+            })
+            .collect();
+
+        let report = SimplifyReport {
+            folded,
+            instructions_before: self.instructions.len(),
+            instructions_after: instructions.len(),
+            gates_before: count_gates(&self.instructions),
+            gates_after: count_gates(&instructions),
+        };
+
+        Ok((Circuit { instructions }, report))
+    }
+}
+
+fn fold(n: u64) -> (Signal, Value) {
+    (Signal::Single(SingleSignal::Literal(n)), Value::Constant(n))
+}
+
+fn count_gates(instructions: &[Instruction]) -> usize {
+    instructions
+        .iter()
+        .filter(|instruction| !matches!(instruction.signal, Signal::Single(_)))
+        .count()
+}
+
+#[cfg(test)]
+mod simplify_tests {
+    use super::*;
+    use crate::circuit::circuit;
+    use crate::parsing::parse_lines;
+
+    #[test]
+    fn fold_constants() {
+        let (simplified, report) = circuit(&["3 -> x", "x LSHIFT 2 -> y", "NOT y -> a"])
+            .simplify(&[], &["a"])
+            .unwrap();
+
+        assert_eq!(simplified.to_string(), "65523 -> a\n");
+        assert_eq!(
+            report,
+            SimplifyReport {
+                folded: 2,
+                instructions_before: 3,
+                instructions_after: 1,
+                gates_before: 2,
+                gates_after: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn keep_inputs_and_bypass_buffers() {
+        let circuit = circuit(&[
+            "1 -> b",
+            "b AND 3 -> x",
+            "x -> y",
+            "y -> z",
+            "z OR c -> a",
+            "2 -> c",
+        ]);
+
+        let (simplified, report) = circuit.simplify(&["b"], &["a"]).unwrap();
+
+        assert_eq!(
+            simplified.to_string(),
+            "1 -> b\nb AND 3 -> x\nx OR 2 -> a\n"
+        );
+        assert_eq!(report.folded, 0);
+        assert_eq!(report.gates_removed(), 0);
+    }
+
+    #[test]
+    fn drop_wires_no_output_reads() {
+        let circuit = circuit(&["1 -> x", "x AND 2 -> y", "3 -> z", "z -> a", "y -> b"]);
+        let (simplified, _) = circuit.simplify(&["x"], &["a"]).unwrap();
+
+        assert_eq!(simplified.to_string(), "3 -> a\n");
+    }
+
+    #[test]
+    fn simplified_puzzle_input_gives_same_signals() {
+        let circuit = Circuit {
+            instructions: parse_lines(include_str!("../bin/circuit_input"), str::parse)
+                .collect::<Result<_, _>>()
+                .unwrap(),
+        };
+
+        let (simplified, report) = circuit.simplify(&["b"], &["a"]).unwrap();
+        assert!(report.gates_after < report.gates_before);
+
+        for &b in &[0, 1, 956, 0xFFFF] {
+            assert_eq!(
+                simplified.emulate_with_overrides(&[("b", b)]).unwrap()["a"],
+                circuit.emulate_with_overrides(&[("b", b)]).unwrap()["a"],
+            );
+        }
+    }
+
+    #[test]
+    fn report_missing_output() {
+        assert_eq!(
+            circuit(&["1 -> x"]).simplify(&[], &["a"]).unwrap_err(),
+            [CircuitError::UndefinedWire("a".to_string())],
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use super::netlist::Netlist;
+use super::plan::CompiledSignal;
+use super::word::Word;
+use super::{Circuit, CircuitError};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Settling {
+    Stable {
+        steps: usize,
+    },
+    /// `wires` are the ones that keep changing.
+    Oscillating {
+        from: usize,
+        period: usize,
+        wires: Vec<String>,
+    },
+    Unsettled {
+        steps: usize,
+    },
+}
+
+impl fmt::Display for Settling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stable { steps } => write!(f, "settled after {} steps", steps),
+            Self::Oscillating {
+                from,
+                period,
+                wires,
+            } => write!(
+                f,
+                "wires {} oscillate every {} steps from step {}",
+                wires.join(", "),
+                period,
+                from,
+            ),
+            Self::Unsettled { steps } => write!(f, "still changing after {} steps", steps),
+        }
+    }
+}
+
+/// Every instruction takes one step to pass a change on.
+#[derive(Debug, Clone)]
+pub struct Simulation<'a, W = u16> {
+    wires: Vec<&'a str>,
+    /// Starts with every wire at zero.
+    states: Vec<Vec<W>>,
+    settling: Settling,
+}
+
+impl Circuit {
+    pub fn simulate(&self, max_steps: usize) -> Result<Simulation<'_>, Vec<CircuitError>> {
+        self.simulate_as(max_steps)
+    }
+
+    pub fn simulate_as<W: Word>(
+        &self,
+        max_steps: usize,
+    ) -> Result<Simulation<'_, W>, Vec<CircuitError>> {
+        let netlist = Netlist::new(self);
+
+        let mut errors = netlist.connection_errors();
+        errors.extend(self.too_wide_literals::<W>());
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let signals: Vec<_> = netlist
+            .drivers
+            .iter()
+            .map(|signal| CompiledSignal::new(signal, &netlist.indices))
+            .collect();
+
+        let mut states = vec![vec![W::from_bits(0); netlist.wires.len()]];
+        let mut steps_seen = HashMap::new();
+        steps_seen.insert(states[0].clone(), 0);
+
+        let settling = loop {
+            let step = states.len();
+            let previous = &states[step - 1];
+
+            if step > max_steps {
+                break Settling::Unsettled { steps: max_steps };
+            }
+
+            let state: Vec<_> = signals
+                .iter()
+                .map(|signal| signal.evaluate(previous))
+                .collect();
+
+            if state == *previous {
+                break Settling::Stable { steps: step - 1 };
+            }
+
+            if let Some(&from) = steps_seen.get(&state) {
+                states.push(state);
+
+                let wires = (0..netlist.wires.len())
+                    .filter(|&idx| states[from..].iter().any(|s| s[idx] != states[from][idx]))
+                    .map(|idx| netlist.wires[idx].to_string())
+                    .collect();
+
+                break Settling::Oscillating {
+                    from,
+                    period: step - from,
+                    wires,
+                };
+            }
+
+            steps_seen.insert(state.clone(), step);
+            states.push(state);
+        };
+
+        Ok(Simulation {
+            wires: netlist.wires,
+            states,
+            settling,
+        })
+    }
+}
+
+impl<'a, W: Word> Simulation<'a, W> {
+    pub fn settling(&self) -> &Settling {
+        &self.settling
+    }
+
+    pub fn steps(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    pub fn values_at(&self, step: usize) -> Option<HashMap<&'a str, W>> {
+        let state = self.states.get(step)?;
+        Some(
+            self.wires
+                .iter()
+                .copied()
+                .zip(state.iter().copied())
+                .collect(),
+        )
+    }
+
+    pub fn write_vcd(&self, mut writer: impl Write) -> io::Result<()> {
+        let ids: Vec<_> = (0..self.wires.len()).map(vcd_id).collect();
+
+        writeln!(writer, "$timescale 1ns $end")?;
+        writeln!(writer, "$scope module circuit $end")?;
+
+        for (wire, id) in self.wires.iter().zip(&ids) {
+            writeln!(writer, "$var wire {} {} {} $end", W::BITS, id, wire)?;
+        }
+
+        writeln!(writer, "$upscope $end")?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        let write_value = |writer: &mut dyn Write, value: W, id: &str| {
+            if W::BITS == 1 {
+                writeln!(writer, "{}{}", value.to_bits(), id)
+            } else {
+                writeln!(writer, "b{:b} {}", value.to_bits(), id)
+            }
+        };
+
+        writeln!(writer, "#0")?;
+        writeln!(writer, "$dumpvars")?;
+
+        for (value, id) in self.states[0].iter().zip(&ids) {
+            write_value(&mut writer, *value, id)?;
+        }
+
+        writeln!(writer, "$end")?;
+
+        for (step, states) in self.states.windows(2).enumerate() {
+            writeln!(writer, "#{}", step + 1)?;
+
+            for ((previous, value), id) in states[0].iter().zip(&states[1]).zip(&ids) {
+                if previous != value {
+                    write_value(&mut writer, *value, id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn vcd_id(mut idx: usize) -> String {
+    const FIRST: u8 = b'!';
+    const NUM_CHARS: usize = (b'~' - FIRST + 1) as usize;
+
+    let mut id = String::new();
+
+    loop {
+        id.push((FIRST + (idx % NUM_CHARS) as u8) as char);
+        idx /= NUM_CHARS;
+
+        if idx == 0 {
+            return id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod simulate_tests {
+    use super::*;
+    use crate::circuit::circuit;
+
+    #[test]
+    fn settle_one_gate_per_step() {
+        let circuit = circuit(&["y AND 5 -> z", "x -> y", "7 -> x"]);
+        let simulation = circuit.simulate(100).unwrap();
+
+        assert_eq!(simulation.settling(), &Settling::Stable { steps: 3 });
+        assert_eq!(simulation.values_at(1).unwrap()["x"], 7);
+        assert_eq!(simulation.values_at(1).unwrap()["y"], 0);
+        assert_eq!(simulation.values_at(2).unwrap()["y"], 7);
+        assert_eq!(simulation.values_at(2).unwrap()["z"], 0);
+        assert_eq!(simulation.values_at(3).unwrap(), circuit.emulate().unwrap(),);
+        assert_eq!(simulation.values_at(4), None);
+    }
+
+    #[test]
+    fn settle_with_feedback() {
+        let circuit = circuit(&["a OR b -> a", "0 -> b"]);
+
+        assert_eq!(
+            circuit.simulate(100).unwrap().settling(),
+            &Settling::Stable { steps: 0 },
+        );
+    }
+
+    #[test]
+    fn detect_oscillation() {
+        let circuit = circuit(&["NOT c -> a", "NOT a -> b", "NOT b -> c", "1 -> d"]);
+        let simulation = circuit.simulate(100).unwrap();
+
+        assert_eq!(
+            simulation.settling(),
+            &Settling::Oscillating {
+                from: 1,
+                period: 2,
+                wires: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            },
+        );
+        assert_eq!(simulation.steps(), 3);
+    }
+
+    #[test]
+    fn give_up_after_max_steps() {
+        let circuit = circuit(&["a ADD 1 -> a"]);
+        let simulation = circuit.simulate(10).unwrap();
+
+        assert_eq!(simulation.settling(), &Settling::Unsettled { steps: 10 });
+        assert_eq!(simulation.values_at(10).unwrap()["a"], 10);
+    }
+
+    #[test]
+    fn report_undefined_wires_but_not_cycles() {
+        assert_eq!(
+            circuit(&["a AND x -> a"]).simulate(10).unwrap_err(),
+            [CircuitError::UndefinedWire("x".to_string())],
+        );
+    }
+
+    #[test]
+    fn write_vcd() {
+        let circuit = circuit(&["5 -> x", "NOT x -> y"]);
+        let mut vcd = Vec::new();
+
+        circuit
+            .simulate_as::<u8>(10)
+            .unwrap()
+            .write_vcd(&mut vcd)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(vcd).unwrap(),
+            "$timescale 1ns $end
+$scope module circuit $end
+$var wire 8 ! x $end
+$var wire 8 \" y $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+b0 !
+b0 \"
+$end
+#1
+b101 !
+b11111111 \"
+#2
+b11111010 \"
+",
+        );
+    }
+
+    #[test]
+    fn give_each_wire_its_own_vcd_id() {
+        let ids: std::collections::HashSet<_> = (0..10_000).map(vcd_id).collect();
+
+        assert_eq!(ids.len(), 10_000);
+        assert!(ids
+            .iter()
+            .flat_map(|id| id.chars())
+            .all(|c| c.is_ascii_graphic()));
+    }
+}
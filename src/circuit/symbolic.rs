@@ -0,0 +1,495 @@
+use super::bdd::{Bdd, Manager, TooManyNodes, FALSE, TRUE};
+use super::word::{self, Word};
+use super::{Circuit, CircuitError, Gate, Signal, SingleSignal};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+pub const MAX_SEARCH_BITS: u32 = 24;
+
+#[derive(Debug)]
+pub struct Symbolic<'a, W = u16> {
+    inputs: Vec<&'a str>,
+    /// Each node only refers to nodes before it.
+    nodes: Vec<Node>,
+    wires: HashMap<&'a str, usize>,
+    word: PhantomData<W>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Constant(u64),
+    Input(usize),
+    Not(usize),
+    WithGate { lhs: usize, rhs: usize, gate: Gate },
+}
+
+impl Circuit {
+    pub fn symbolic(&self, inputs: &[&str]) -> Result<Symbolic<'_>, Vec<CircuitError>> {
+        self.symbolic_as(inputs)
+    }
+
+    /// Whatever normally drives `inputs` is ignored, so they are free.
+    pub fn symbolic_as<W: Word>(
+        &self,
+        inputs: &[&str],
+    ) -> Result<Symbolic<'_, W>, Vec<CircuitError>> {
+        let (netlist, order) = self.validate_as::<W>()?;
+
+        let mut nodes = Nodes::default();
+        let mut wire_nodes = vec![None; netlist.wires.len()];
+        let mut input_wires = Vec::with_capacity(inputs.len());
+
+        for (input_idx, &input) in inputs.iter().enumerate() {
+            let (&wire, &idx) = netlist
+                .indices
+                .get_key_value(input)
+                .ok_or_else(|| vec![CircuitError::UndefinedWire(input.to_string())])?;
+
+            wire_nodes[idx] = Some(nodes.intern::<W>(Node::Input(input_idx)));
+            input_wires.push(wire);
+        }
+
+        for idx in order {
+            if wire_nodes[idx].is_some() {
+                continue;
+            }
+
+            let operand = |nodes: &mut Nodes, single_signal: &SingleSignal| match single_signal {
+                SingleSignal::Literal(n) => nodes.intern::<W>(Node::Constant(*n)),
+                SingleSignal::Wire(wire) => wire_nodes[netlist.indices[wire.as_str()]].unwrap(),
+            };
+
+            let node = match netlist.drivers[idx] {
+                Signal::WithGate { lhs, rhs, gate } => {
+                    let node = Node::WithGate {
+                        lhs: operand(&mut nodes, lhs),
+                        rhs: operand(&mut nodes, rhs),
+                        gate: *gate,
+                    };
+
+                    nodes.intern::<W>(node)
+                }
+                Signal::Not { signal } => {
+                    let node = Node::Not(operand(&mut nodes, signal));
+                    nodes.intern::<W>(node)
+                }
+                Signal::Single(single_signal) => operand(&mut nodes, single_signal),
+            };
+
+            wire_nodes[idx] = Some(node);
+        }
+
+        let wires = netlist
+            .wires
+            .iter()
+            .zip(wire_nodes)
+            .map(|(&wire, node)| (wire, node.unwrap()))
+            .collect();
+
+        Ok(Symbolic {
+            inputs: input_wires,
+            nodes: nodes.nodes,
+            wires,
+            word: PhantomData,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Nodes {
+    nodes: Vec<Node>,
+    indices: HashMap<Node, usize>,
+}
+
+impl Nodes {
+    /// Folds `node` into a constant first if all its operands are constant.
+    fn intern<W: Word>(&mut self, node: Node) -> usize {
+        let node = self.fold::<W>(node).unwrap_or(node);
+        let nodes = &mut self.nodes;
+
+        *self.indices.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    fn fold<W: Word>(&self, node: Node) -> Option<Node> {
+        let constant = |idx: usize| match self.nodes[idx] {
+            Node::Constant(n) => Some(n),
+            _ => None,
+        };
+
+        let n = match node {
+            Node::WithGate { lhs, rhs, gate } => {
+                gate.apply_to(constant(lhs)?, constant(rhs)?, W::BITS)
+            }
+            Node::Not(operand) => !constant(operand)? & word::mask(W::BITS),
+            Node::Constant(_) | Node::Input(_) => return None,
+        };
+
+        Some(Node::Constant(n))
+    }
+}
+
+impl<'a, W: Word> Symbolic<'a, W> {
+    pub fn inputs(&self) -> &[&'a str] {
+        &self.inputs
+    }
+
+    pub fn wire(&self, wire: &str) -> Option<Expr<'_, 'a, W>> {
+        let root = *self.wires.get(wire)?;
+
+        let mut is_in_cone = vec![false; root + 1];
+        is_in_cone[root] = true;
+
+        for idx in (0..=root).rev() {
+            if is_in_cone[idx] {
+                for operand in operands(self.nodes[idx]) {
+                    is_in_cone[operand] = true;
+                }
+            }
+        }
+
+        Some(Expr {
+            symbolic: self,
+            cone: (0..=root).filter(|&idx| is_in_cone[idx]).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr<'s, 'a, W = u16> {
+    symbolic: &'s Symbolic<'a, W>,
+    /// Ends with the root.
+    cone: Vec<usize>,
+}
+
+impl<'s, 'a, W: Word> Expr<'s, 'a, W> {
+    pub fn as_constant(&self) -> Option<W> {
+        match self.symbolic.nodes[self.root()] {
+            Node::Constant(n) => Some(W::from_bits(n)),
+            _ => None,
+        }
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.input_indices()
+            .map(move |idx| self.symbolic.inputs[idx])
+    }
+
+    /// Panics if there are fewer values than inputs.
+    pub fn evaluate(&self, inputs: &[W]) -> W {
+        let inputs: Vec<_> = inputs.iter().map(|input| input.to_bits()).collect();
+        let mut values = vec![0; self.root() + 1];
+
+        W::from_bits(self.evaluate_bits(&inputs, &mut values))
+    }
+
+    pub fn solve(
+        &self,
+        target: W,
+    ) -> Result<impl Iterator<Item = Vec<(&'a str, W)>> + '_, SearchSpaceTooLarge> {
+        let input_indices: Vec<_> = self.input_indices().collect();
+        let bits = input_indices.len() as u32 * W::BITS;
+
+        if bits > MAX_SEARCH_BITS {
+            return Err(SearchSpaceTooLarge { bits });
+        }
+
+        let mask = word::mask(W::BITS);
+        let mut inputs = vec![0; self.symbolic.inputs.len()];
+        let mut values = vec![0; self.root() + 1];
+
+        Ok((0..1_u64 << bits).filter_map(move |assignment| {
+            for (position, &idx) in input_indices.iter().enumerate() {
+                inputs[idx] = assignment >> (position as u32 * W::BITS) & mask;
+            }
+
+            if self.evaluate_bits(&inputs, &mut values) != target.to_bits() {
+                return None;
+            }
+
+            let solution = input_indices
+                .iter()
+                .map(|&idx| (self.symbolic.inputs[idx], W::from_bits(inputs[idx])))
+                .collect();
+
+            Some(solution)
+        }))
+    }
+
+    /// Bit `bit` of input `input` is variable `var(input, bit)`. `blasted` is
+    /// shared between expressions over the same circuit.
+    pub(super) fn bit_blast(
+        &self,
+        manager: &mut Manager,
+        blasted: &mut HashMap<usize, Vec<Bdd>>,
+        var: impl Fn(usize, u32) -> u32,
+    ) -> Result<Vec<Bdd>, TooManyNodes> {
+        for &idx in &self.cone {
+            if blasted.contains_key(&idx) {
+                continue;
+            }
+
+            let bits = match self.symbolic.nodes[idx] {
+                Node::Constant(n) => (0..W::BITS)
+                    .map(|bit| if n >> bit & 1 == 1 { TRUE } else { FALSE })
+                    .collect(),
+                Node::Input(input) => (0..W::BITS)
+                    .map(|bit| manager.var(var(input, bit)))
+                    .collect::<Result<_, _>>()?,
+                Node::Not(operand) => manager.not_word(&blasted[&operand])?,
+                Node::WithGate { lhs, rhs, gate } => {
+                    manager.apply_gate(gate, &blasted[&lhs], &blasted[&rhs])?
+                }
+            };
+
+            blasted.insert(idx, bits);
+        }
+
+        Ok(blasted[&self.root()].clone())
+    }
+
+    fn root(&self) -> usize {
+        *self.cone.last().unwrap()
+    }
+
+    fn input_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cone
+            .iter()
+            .filter_map(move |&idx| match self.symbolic.nodes[idx] {
+                Node::Input(input) => Some(input),
+                _ => None,
+            })
+    }
+
+    fn evaluate_bits(&self, inputs: &[u64], values: &mut [u64]) -> u64 {
+        for &idx in &self.cone {
+            values[idx] = match self.symbolic.nodes[idx] {
+                Node::Constant(n) => n,
+                Node::Input(input) => inputs[input],
+                Node::Not(operand) => !values[operand] & word::mask(W::BITS),
+                Node::WithGate { lhs, rhs, gate } => {
+                    gate.apply_to(values[lhs], values[rhs], W::BITS)
+                }
+            };
+        }
+
+        values[self.root()]
+    }
+}
+
+impl<W: Word> fmt::Display for Expr<'_, '_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MAX_NESTING: usize = 4;
+
+        let nodes = &self.symbolic.nodes;
+        let mut num_uses = vec![0; self.root() + 1];
+
+        for &idx in &self.cone {
+            for operand in operands(nodes[idx]) {
+                num_uses[operand] += 1;
+            }
+        }
+
+        // How deeply each node nests unbound gates, and whether it is bound.
+        let mut nesting = vec![0; self.root() + 1];
+        let mut is_bound = vec![false; self.root() + 1];
+
+        for &idx in &self.cone {
+            if let Node::Constant(_) | Node::Input(_) = nodes[idx] {
+                continue;
+            }
+
+            let operand_nesting = operands(nodes[idx]).map(|operand| nesting[operand]);
+            nesting[idx] = 1 + operand_nesting.max().unwrap_or(0);
+
+            if num_uses[idx] > 1 || nesting[idx] >= MAX_NESTING {
+                is_bound[idx] = true;
+                nesting[idx] = 0;
+            }
+        }
+
+        for &idx in self.cone.iter().filter(|&&idx| is_bound[idx]) {
+            writeln!(f, "let t{} = {};", idx, self.render(idx, &is_bound))?;
+        }
+
+        write!(f, "{}", self.render(self.root(), &is_bound))
+    }
+}
+
+impl<W: Word> Expr<'_, '_, W> {
+    fn render(&self, idx: usize, is_bound: &[bool]) -> String {
+        match self.symbolic.nodes[idx] {
+            Node::Constant(n) => n.to_string(),
+            Node::Input(input) => self.symbolic.inputs[input].to_string(),
+            Node::Not(signal) => format!("NOT {}", self.render_operand(signal, is_bound)),
+            Node::WithGate { lhs, rhs, gate } => format!(
+                "{} {} {}",
+                self.render_operand(lhs, is_bound),
+                gate,
+                self.render_operand(rhs, is_bound),
+            ),
+        }
+    }
+
+    fn render_operand(&self, idx: usize, is_bound: &[bool]) -> String {
+        match self.symbolic.nodes[idx] {
+            Node::Constant(_) | Node::Input(_) => self.render(idx, is_bound),
+            _ if is_bound[idx] => format!("t{}", idx),
+            _ => format!("({})", self.render(idx, is_bound)),
+        }
+    }
+}
+
+fn operands(node: Node) -> impl Iterator<Item = usize> {
+    let (first, second) = match node {
+        Node::Constant(_) | Node::Input(_) => (None, None),
+        Node::Not(operand) => (Some(operand), None),
+        Node::WithGate { lhs, rhs, .. } => (Some(lhs), Some(rhs)),
+    };
+
+    first.into_iter().chain(second)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SearchSpaceTooLarge {
+    pub bits: u32,
+}
+
+impl fmt::Display for SearchSpaceTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the inputs have {} bits between them, but at most {} can be searched",
+            self.bits, MAX_SEARCH_BITS,
+        )
+    }
+}
+
+impl std::error::Error for SearchSpaceTooLarge {}
+
+#[cfg(test)]
+mod symbolic_tests {
+    use super::*;
+    use crate::circuit::{circuit, wire_name};
+    use crate::parsing::parse_lines;
+    use std::iter;
+
+    #[test]
+    fn express_wire_over_inputs() {
+        let circuit = circuit(&["x AND y -> z", "z LSHIFT 2 -> w", "3 -> y", "5 -> x"]);
+        let symbolic = circuit.symbolic(&["x"]).unwrap();
+        let w = symbolic.wire("w").unwrap();
+
+        assert_eq!(w.to_string(), "(x AND 3) LSHIFT 2");
+        assert_eq!(w.inputs().collect::<Vec<_>>(), ["x"]);
+        assert_eq!(w.evaluate(&[5]), 4);
+        assert_eq!(w.as_constant(), None);
+    }
+
+    #[test]
+    fn fold_wires_that_do_not_depend_on_inputs() {
+        let circuit = circuit(&["x AND y -> z", "z LSHIFT 2 -> w", "3 -> y", "5 -> x"]);
+        let symbolic = circuit.symbolic(&[]).unwrap();
+
+        assert_eq!(symbolic.wire("w").unwrap().as_constant(), Some(4));
+        assert_eq!(symbolic.wire("w").unwrap().to_string(), "4");
+    }
+
+    #[test]
+    fn share_common_subexpressions() {
+        let circuit = circuit(&[
+            "1 -> b",
+            "b RSHIFT 1 -> c",
+            "b RSHIFT 1 -> d",
+            "c XOR d -> e",
+        ]);
+        let symbolic = circuit.symbolic(&["b"]).unwrap();
+
+        assert_eq!(
+            symbolic.wire("e").unwrap().to_string(),
+            "let t2 = b RSHIFT 1;\nt2 XOR t2",
+        );
+    }
+
+    #[test]
+    fn bind_deeply_nested_subexpressions() {
+        let instructions = iter::once("1 -> a".to_string())
+            .chain(
+                (1..100_000).map(|idx| format!("NOT {} -> {}", wire_name(idx - 1), wire_name(idx))),
+            )
+            .collect::<Vec<_>>();
+
+        let circuit = circuit(&instructions.iter().map(String::as_str).collect::<Vec<_>>());
+        let symbolic = circuit.symbolic(&["a"]).unwrap();
+        let rendered = symbolic.wire(&wire_name(99_999)).unwrap().to_string();
+
+        assert!(rendered
+            .lines()
+            .all(|line| line.matches("NOT").count() <= 4));
+        assert_eq!(rendered.lines().count(), 25_000);
+    }
+
+    #[test]
+    fn solve_for_input() {
+        let circuit = circuit(&["x ADD 3 -> y", "0 -> x"]);
+        let symbolic = circuit.symbolic_as::<u8>(&["x"]).unwrap();
+        let y = symbolic.wire("y").unwrap();
+
+        assert_eq!(y.solve(10).unwrap().collect::<Vec<_>>(), [vec![("x", 7)]]);
+        assert_eq!(y.solve(2).unwrap().collect::<Vec<_>>(), [vec![("x", 255)]]);
+    }
+
+    #[test]
+    fn solve_for_several_inputs() {
+        let circuit = circuit(&["x XOR y -> z", "0 -> x", "0 -> y", "1 -> w"]);
+        let symbolic = circuit.symbolic_as::<u8>(&["w", "x", "y"]).unwrap();
+        let solutions: Vec<_> = symbolic.wire("z").unwrap().solve(0xFF).unwrap().collect();
+
+        assert_eq!(solutions.len(), 256);
+        assert!(solutions.contains(&vec![("x", 0x0F), ("y", 0xF0)]));
+    }
+
+    #[test]
+    fn solve_puzzle_input_for_b() {
+        let circuit = Circuit {
+            instructions: parse_lines(include_str!("../bin/circuit_input"), str::parse)
+                .collect::<Result<_, _>>()
+                .unwrap(),
+        };
+
+        let a = circuit.emulate().unwrap()["a"];
+        let overridden_a = circuit.emulate_with_overrides(&[("b", a)]).unwrap()["a"];
+
+        let symbolic = circuit.symbolic(&["b"]).unwrap();
+        let solutions: Vec<_> = symbolic
+            .wire("a")
+            .unwrap()
+            .solve(overridden_a)
+            .unwrap()
+            .collect();
+
+        assert!(solutions.contains(&vec![("b", a)]));
+    }
+
+    #[test]
+    fn reject_search_through_too_many_bits() {
+        let circuit = circuit(&["x AND y -> z", "0 -> x", "0 -> y"]);
+        let symbolic = circuit.symbolic(&["x", "y"]).unwrap();
+
+        assert_eq!(
+            symbolic.wire("z").unwrap().solve(0).err(),
+            Some(SearchSpaceTooLarge { bits: 32 }),
+        );
+    }
+
+    #[test]
+    fn report_missing_input() {
+        assert_eq!(
+            circuit(&["1 -> a"]).symbolic(&["b"]).unwrap_err(),
+            [CircuitError::UndefinedWire("b".to_string())],
+        );
+    }
+}
@@ -0,0 +1,748 @@
+use super::{Circuit, CircuitError, Gate, Instruction, Signal, SingleSignal};
+use crate::parsing::{
+    all_consuming, alt, extract_lowercase, tag, take_while1, unsigned, ParseError,
+};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Keywords that could name a wire, which must be escaped.
+const KEYWORDS: &str = "\
+    always and assign automatic begin buf case casex casez cell cmos config deassign default \
+    defparam design disable edge else end endcase endconfig endfunction endgenerate endmodule \
+    endprimitive endspecify endtable endtask event for force forever fork function generate genvar \
+    if ifnone incdir include initial inout input instance integer join large liblist library \
+    localparam macromodule medium module nand negedge nmos nor not or output parameter pmos \
+    posedge primitive pulldown pullup rcmos real realtime reg release repeat rnmos rpmos rtran \
+    scalared signed small specify specparam table task time tran tri triand trior trireg unsigned \
+    use uwire vectored wait wand while wire wor xnor xor";
+
+/// Verilog has no rotation operators, so rotations call these functions.
+const ROTATE_LEFT: &str = "rotate_left";
+const ROTATE_RIGHT: &str = "rotate_right";
+
+pub struct Verilog<'c> {
+    circuit: &'c Circuit,
+    inputs: HashSet<&'c str>,
+}
+
+impl Circuit {
+    pub fn to_verilog(&self) -> Result<Verilog<'_>, Vec<CircuitError>> {
+        let errors = self.too_wide_literals::<u16>();
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Verilog {
+            circuit: self,
+            inputs: HashSet::new(),
+        })
+    }
+
+    /// Only reads the subset [`Circuit::to_verilog`] writes. Input ports are
+    /// driven by zero.
+    pub fn from_verilog(s: &str) -> Result<Self, ParseError> {
+        all_consuming(module, s)
+    }
+}
+
+impl<'c> Verilog<'c> {
+    pub fn with_inputs(mut self, inputs: &[&str]) -> Result<Self, CircuitError> {
+        for &input in inputs {
+            let instruction = self
+                .circuit
+                .instructions
+                .iter()
+                .find(|instruction| instruction.wire == input)
+                .ok_or_else(|| CircuitError::UndefinedWire(input.to_string()))?;
+
+            self.inputs.insert(&instruction.wire);
+        }
+
+        Ok(self)
+    }
+
+    fn instructions(&self) -> impl Iterator<Item = &'c Instruction> + '_ {
+        self.circuit
+            .instructions
+            .iter()
+            .filter(move |instruction| !self.inputs.contains(instruction.wire.as_str()))
+    }
+}
+
+impl fmt::Display for Verilog<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut wires = Vec::new();
+        let mut seen = HashSet::new();
+        let mut read = HashSet::new();
+
+        // Input ports are listed even if nothing reads them.
+        for instruction in &self.circuit.instructions {
+            if !self.inputs.contains(instruction.wire.as_str()) {
+                for wire in instruction.signal.wires() {
+                    read.insert(wire);
+
+                    if seen.insert(wire) {
+                        wires.push(wire);
+                    }
+                }
+            }
+
+            if seen.insert(&instruction.wire) {
+                wires.push(&instruction.wire);
+            }
+        }
+
+        let driven: HashSet<_> = self
+            .instructions()
+            .map(|instruction| instruction.wire.as_str())
+            .collect();
+        let is_output = |wire: &str| driven.contains(wire) && !read.contains(wire);
+
+        let inputs = wires.iter().filter(|&&wire| self.inputs.contains(wire));
+        let outputs = wires.iter().filter(|&&wire| is_output(wire));
+
+        let ports: Vec<_> = inputs
+            .map(|wire| format!("input [15:0] {}", Identifier(wire)))
+            .chain(outputs.map(|wire| format!("output [15:0] {}", Identifier(wire))))
+            .collect();
+
+        if ports.is_empty() {
+            writeln!(f, "module circuit;")?;
+        } else {
+            writeln!(f, "module circuit(\n  {}\n);", ports.join(",\n  "))?;
+        }
+
+        let rotations = self
+            .instructions()
+            .filter_map(|instruction| match instruction.signal {
+                Signal::WithGate {
+                    gate: Gate::LRotate,
+                    ..
+                } => Some((ROTATE_LEFT, "<<", ">>")),
+                Signal::WithGate {
+                    gate: Gate::RRotate,
+                    ..
+                } => Some((ROTATE_RIGHT, ">>", "<<")),
+                _ => None,
+            });
+
+        let mut declared = HashSet::new();
+
+        for (function, towards, away) in rotations {
+            if declared.insert(function) {
+                writeln!(
+                    f,
+                    "  function [15:0] {0}(input [15:0] x, input [15:0] y);\n    \
+                     {0} = x {1} y % 16 | x {2} (16 - y % 16) % 16;\n  \
+                     endfunction",
+                    function, towards, away,
+                )?;
+            }
+        }
+
+        for wire in wires {
+            if !self.inputs.contains(wire) && !is_output(wire) {
+                writeln!(f, "  wire [15:0] {};", Identifier(wire))?;
+            }
+        }
+
+        for instruction in self.instructions() {
+            write!(f, "  assign {} = ", Identifier(&instruction.wire))?;
+
+            match &instruction.signal {
+                Signal::WithGate { lhs, rhs, gate } => {
+                    let (lhs, rhs) = (Operand(lhs), Operand(rhs));
+
+                    match gate {
+                        Gate::And => write!(f, "{} & {}", lhs, rhs),
+                        Gate::Or => write!(f, "{} | {}", lhs, rhs),
+                        Gate::Xor => write!(f, "{} ^ {}", lhs, rhs),
+                        Gate::Nand => write!(f, "~({} & {})", lhs, rhs),
+                        Gate::Nor => write!(f, "~({} | {})", lhs, rhs),
+                        Gate::Xnor => write!(f, "~({} ^ {})", lhs, rhs),
+                        Gate::LShift => write!(f, "{} << {}", lhs, rhs),
+                        Gate::RShift => write!(f, "{} >> {}", lhs, rhs),
+                        Gate::LRotate => write!(f, "{}({}, {})", ROTATE_LEFT, lhs, rhs),
+                        Gate::RRotate => write!(f, "{}({}, {})", ROTATE_RIGHT, lhs, rhs),
+                        Gate::Add => write!(f, "{} + {}", lhs, rhs),
+                        Gate::Sub => write!(f, "{} - {}", lhs, rhs),
+                        Gate::Mul => write!(f, "{} * {}", lhs, rhs),
+                    }?
+                }
+                Signal::Not { signal } => write!(f, "~{}", Operand(signal))?,
+                Signal::Single(single_signal) => write!(f, "{}", Operand(single_signal))?,
+            }
+
+            writeln!(f, ";")?;
+        }
+
+        writeln!(f, "endmodule")
+    }
+}
+
+struct Identifier<'a>(&'a str);
+
+impl fmt::Display for Identifier<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_keyword(self.0) {
+            write!(f, "\\{} ", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+struct Operand<'a>(&'a SingleSignal);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            SingleSignal::Literal(n) => write!(f, "16'd{}", n),
+            SingleSignal::Wire(wire) => write!(f, "{}", Identifier(wire)),
+        }
+    }
+}
+
+fn module(s: &str) -> Result<(&str, Circuit), ParseError> {
+    let s = keyword("module", s)?;
+    let (s, _) = identifier(s)?;
+
+    let mut instructions = Vec::new();
+    let s = ports(s, &mut instructions)?;
+    let mut s = token(";", s)?;
+
+    loop {
+        if let Ok(s) = keyword("endmodule", s) {
+            return Ok((skip(s), Circuit { instructions }));
+        }
+
+        s = if let Ok(s) = keyword("assign", s) {
+            let (s, wire) = identifier(s)?;
+            let s = token("=", s)?;
+            let (s, signal) = Signal::new_verilog(s)?;
+
+            instructions.push(Instruction {
+                signal,
+                wire: wire.to_string(),
+            });
+
+            token(";", s)?
+        } else if let Ok(s) = keyword("function", s) {
+            rotation_function(s)?
+        } else if let Ok((s, direction)) = direction(&["input", "output", "wire"], s) {
+            declaration(s, direction, &mut instructions)?
+        } else {
+            let s = skip(s);
+
+            return Err(
+                ["endmodule", "assign", "function", "input", "output", "wire"]
+                    .iter()
+                    .map(|keyword| ParseError::new(s, format!("{:?}", keyword)))
+                    .reduce(ParseError::or)
+                    .unwrap(),
+            );
+        };
+    }
+}
+
+fn ports<'a>(s: &'a str, instructions: &mut Vec<Instruction>) -> Result<&'a str, ParseError> {
+    let mut s = match token("(", s) {
+        Ok(s) => s,
+        Err(_) => return Ok(s),
+    };
+
+    if let Ok(s) = token(")", s) {
+        return Ok(s);
+    }
+
+    let mut port_direction = None;
+
+    loop {
+        if let Ok((new_s, direction)) = direction(&["input", "output"], s) {
+            s = range(new_s)?;
+            port_direction = Some(direction);
+        }
+
+        let (new_s, wire) = identifier(s)?;
+        s = new_s;
+
+        if port_direction == Some("input") {
+            instructions.push(input(wire));
+        }
+
+        match token(",", s) {
+            Ok(new_s) => s = new_s,
+            Err(error) => return token(")", s).map_err(|other| error.or(other)),
+        }
+    }
+}
+
+fn declaration<'a>(
+    s: &'a str,
+    direction: &str,
+    instructions: &mut Vec<Instruction>,
+) -> Result<&'a str, ParseError> {
+    let mut s = range(s)?;
+
+    loop {
+        let (new_s, wire) = identifier(s)?;
+
+        if direction == "input" {
+            instructions.push(input(wire));
+        }
+
+        match token(",", new_s) {
+            Ok(new_s) => s = new_s,
+            Err(error) => return token(";", new_s).map_err(|other| error.or(other)),
+        }
+    }
+}
+
+fn input(wire: &str) -> Instruction {
+    Instruction {
+        signal: Signal::Single(SingleSignal::Literal(0)),
+        wire: wire.to_string(),
+    }
+}
+
+fn direction<'a>(
+    directions: &[&'static str],
+    s: &'a str,
+) -> Result<(&'a str, &'static str), ParseError> {
+    directions
+        .iter()
+        .map(|&direction| keyword(direction, s).map(|s| (s, direction)))
+        .reduce(|parsed, other| parsed.or_else(|error| other.map_err(|other| error.or(other))))
+        .unwrap()
+}
+
+fn range(s: &str) -> Result<&str, ParseError> {
+    let s = token("[", s)?;
+    let s = token("15", s)?;
+    let s = token(":", s)?;
+    let s = token("0", s)?;
+    token("]", s)
+}
+
+fn rotation_function(s: &str) -> Result<&str, ParseError> {
+    let s = range(s)?;
+
+    let (s, name, towards, away) = match keyword(ROTATE_LEFT, s) {
+        Ok(s) => (s, ROTATE_LEFT, "<<", ">>"),
+        Err(error) => keyword(ROTATE_RIGHT, s)
+            .map(|s| (s, ROTATE_RIGHT, ">>", "<<"))
+            .map_err(|other| error.or(other))?,
+    };
+
+    // Split into tokens, so that the body may be spaced and commented freely.
+    let expected = format!(
+        "( input [ 15 : 0 ] x , input [ 15 : 0 ] y ) ; \
+         {} = x {} y % 16 | x {} ( 16 - y % 16 ) % 16 ; endfunction",
+        name, towards, away,
+    );
+
+    expected.split_whitespace().try_fold(s, |s, expected| {
+        if expected.starts_with(is_identifier_char) {
+            keyword(expected, s)
+        } else {
+            token(expected, s)
+        }
+    })
+}
+
+impl Signal {
+    fn new_verilog(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                Self::new_negated_gate,
+                Self::new_verilog_not,
+                Self::new_rotation,
+                Self::new_verilog_gate,
+                Self::new_verilog_single,
+            ),
+            s,
+        )
+    }
+
+    fn new_negated_gate(s: &str) -> Result<(&str, Self), ParseError> {
+        let s = token("~", s)?;
+        let s = token("(", s)?;
+        let (s, lhs) = SingleSignal::new_verilog(s)?;
+        let (s, gate) = operator(&[("&", Gate::Nand), ("|", Gate::Nor), ("^", Gate::Xnor)], s)?;
+        let (s, rhs) = SingleSignal::new_verilog(s)?;
+        let s = token(")", s)?;
+
+        Ok((s, Self::WithGate { lhs, rhs, gate }))
+    }
+
+    fn new_verilog_not(s: &str) -> Result<(&str, Self), ParseError> {
+        let s = token("~", s)?;
+        let (s, signal) = SingleSignal::new_verilog(s)?;
+
+        Ok((s, Self::Not { signal }))
+    }
+
+    fn new_rotation(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, gate) = match keyword(ROTATE_LEFT, s) {
+            Ok(s) => (s, Gate::LRotate),
+            Err(error) => keyword(ROTATE_RIGHT, s)
+                .map(|s| (s, Gate::RRotate))
+                .map_err(|other| error.or(other))?,
+        };
+
+        let s = token("(", s)?;
+        let (s, lhs) = SingleSignal::new_verilog(s)?;
+        let s = token(",", s)?;
+        let (s, rhs) = SingleSignal::new_verilog(s)?;
+        let s = token(")", s)?;
+
+        Ok((s, Self::WithGate { lhs, rhs, gate }))
+    }
+
+    fn new_verilog_gate(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, lhs) = SingleSignal::new_verilog(s)?;
+        let (s, gate) = operator(
+            &[
+                ("<<", Gate::LShift),
+                (">>", Gate::RShift),
+                ("~^", Gate::Xnor),
+                ("^~", Gate::Xnor),
+                ("^", Gate::Xor),
+                ("&", Gate::And),
+                ("|", Gate::Or),
+                ("+", Gate::Add),
+                ("-", Gate::Sub),
+                ("*", Gate::Mul),
+            ],
+            s,
+        )?;
+        let (s, rhs) = SingleSignal::new_verilog(s)?;
+
+        Ok((s, Self::WithGate { lhs, rhs, gate }))
+    }
+
+    fn new_verilog_single(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, single_signal) = SingleSignal::new_verilog(s)?;
+        Ok((s, Self::Single(single_signal)))
+    }
+}
+
+/// An operator must come before any that it starts with.
+fn operator<'a>(operators: &[(&str, Gate)], s: &'a str) -> Result<(&'a str, Gate), ParseError> {
+    let s = skip(s);
+
+    operators
+        .iter()
+        .find_map(|&(operator, gate)| Some((tag(operator, s).ok()?, gate)))
+        .ok_or_else(|| {
+            operators
+                .iter()
+                .map(|(operator, _)| ParseError::new(s, format!("{:?}", operator)))
+                .reduce(ParseError::or)
+                .unwrap()
+        })
+}
+
+impl SingleSignal {
+    fn new_verilog(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                |s| number(s).map(|(s, n)| (s, Self::Literal(n))),
+                |s| identifier(s).map(|(s, wire)| (s, Self::Wire(wire.to_string()))),
+            ),
+            s,
+        )
+    }
+}
+
+/// The size itself is not checked.
+fn number(s: &str) -> Result<(&str, u64), ParseError> {
+    let s = skip(s);
+    let size_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+    let s = match tag("'", &s[size_end..]) {
+        Ok(s) => s,
+        Err(_) => return unsigned(s),
+    };
+
+    let (radix, digits) = match s.chars().next().map(|c| c.to_ascii_lowercase()) {
+        Some('b') => (2, &s[1..]),
+        Some('o') => (8, &s[1..]),
+        Some('d') => (10, &s[1..]),
+        Some('h') => (16, &s[1..]),
+        _ => return Err(ParseError::new(s, "one of b, o, d or h")),
+    };
+
+    let (s, extracted) = take_while1(|c| c.is_digit(radix), "digits", digits)?;
+    let n = u64::from_str_radix(extracted, radix)
+        .map_err(|_| ParseError::new(digits, "a number that fits in u64"))?;
+
+    Ok((s, n))
+}
+
+fn identifier(s: &str) -> Result<(&str, &str), ParseError> {
+    let s = skip(s);
+
+    if let Ok(escaped) = tag("\\", s) {
+        let (rest, name) = take_while1(|c| !c.is_whitespace(), "an identifier", escaped)?;
+
+        return if name.chars().all(|c| c.is_ascii_lowercase()) {
+            Ok((rest, name))
+        } else {
+            Err(ParseError::new(escaped, "a wire name of lowercase letters"))
+        };
+    }
+
+    match extract_lowercase(s) {
+        Ok((rest, name)) if !rest.starts_with(is_identifier_char) && !is_keyword(name) => {
+            Ok((rest, name))
+        }
+        _ => Err(ParseError::new(s, "a wire name of lowercase letters")),
+    }
+}
+
+fn keyword<'a>(word: &str, s: &'a str) -> Result<&'a str, ParseError> {
+    let s = skip(s);
+
+    match tag(word, s) {
+        Ok(rest) if !rest.starts_with(is_identifier_char) => Ok(rest),
+        _ => Err(ParseError::new(s, format!("{:?}", word))),
+    }
+}
+
+fn token<'a>(token: &str, s: &'a str) -> Result<&'a str, ParseError> {
+    tag(token, skip(s))
+}
+
+fn is_keyword(name: &str) -> bool {
+    KEYWORDS.split_whitespace().any(|keyword| keyword == name)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+fn skip(mut s: &str) -> &str {
+    loop {
+        s = s.trim_start();
+
+        if let Some(comment) = s.strip_prefix("//") {
+            s = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = s.strip_prefix("/*") {
+            s = comment.find("*/").map_or("", |end| &comment[end + 2..]);
+        } else {
+            return s;
+        }
+    }
+}
+
+#[cfg(test)]
+mod verilog_tests {
+    use super::*;
+    use crate::circuit::circuit;
+    use crate::parsing::parse_lines;
+
+    #[test]
+    fn export_circuit() {
+        let circuit = circuit(&[
+            "123 -> x",
+            "456 -> y",
+            "x AND y -> d",
+            "x NOR y -> e",
+            "x LSHIFT 2 -> f",
+            "y RROTATE 2 -> g",
+            "NOT x -> h",
+            "d -> if",
+        ]);
+
+        assert_eq!(
+            circuit.to_verilog().unwrap().to_string(),
+            "\
+module circuit(
+  output [15:0] e,
+  output [15:0] f,
+  output [15:0] g,
+  output [15:0] h,
+  output [15:0] \\if \n\
+);
+  function [15:0] rotate_right(input [15:0] x, input [15:0] y);
+    rotate_right = x >> y % 16 | x << (16 - y % 16) % 16;
+  endfunction
+  wire [15:0] x;
+  wire [15:0] y;
+  wire [15:0] d;
+  assign x = 16'd123;
+  assign y = 16'd456;
+  assign d = x & y;
+  assign e = ~(x | y);
+  assign f = x << 16'd2;
+  assign g = rotate_right(y, 16'd2);
+  assign h = ~x;
+  assign \\if  = d;
+endmodule
+",
+        );
+    }
+
+    #[test]
+    fn export_with_inputs() {
+        let circuit = circuit(&["1 -> x", "x ADD 2 -> y"]);
+        let verilog = circuit.to_verilog().unwrap().with_inputs(&["x"]).unwrap();
+
+        assert_eq!(
+            verilog.to_string(),
+            "\
+module circuit(
+  input [15:0] x,
+  output [15:0] y
+);
+  assign y = x + 16'd2;
+endmodule
+",
+        );
+
+        let imported = Circuit::from_verilog(&verilog.to_string()).unwrap();
+        assert_eq!(imported.to_string(), "0 -> x\nx ADD 2 -> y\n");
+    }
+
+    #[test]
+    fn export_input_that_nothing_reads() {
+        let circuit = circuit(&["1 -> x", "2 -> y"]);
+        let verilog = circuit.to_verilog().unwrap().with_inputs(&["x"]).unwrap();
+
+        assert_eq!(
+            verilog.to_string(),
+            "\
+module circuit(
+  input [15:0] x,
+  output [15:0] y
+);
+  assign y = 16'd2;
+endmodule
+",
+        );
+    }
+
+    #[test]
+    fn reject_export_of_too_wide_literal() {
+        let circuit = circuit(&["65535 -> x", "65536 -> y"]);
+
+        assert_eq!(
+            circuit.to_verilog().err(),
+            Some(vec![CircuitError::LiteralTooWide {
+                literal: 65536,
+                bits: 16,
+            }]),
+        );
+    }
+
+    #[test]
+    fn round_trip_puzzle_input() {
+        let circuit = Circuit {
+            instructions: parse_lines(include_str!("../bin/circuit_input"), str::parse)
+                .collect::<Result<_, _>>()
+                .unwrap(),
+        };
+
+        let imported = Circuit::from_verilog(&circuit.to_verilog().unwrap().to_string()).unwrap();
+        assert_eq!(imported.instructions, circuit.instructions);
+    }
+
+    #[test]
+    fn round_trip_every_gate() {
+        let circuit = circuit(&[
+            "5 -> x",
+            "3 -> y",
+            "x AND y -> a",
+            "x OR y -> b",
+            "x XOR y -> c",
+            "x NAND y -> d",
+            "x NOR y -> e",
+            "x XNOR y -> f",
+            "x LSHIFT y -> g",
+            "x RSHIFT y -> h",
+            "x LROTATE y -> i",
+            "x RROTATE y -> j",
+            "x ADD y -> k",
+            "x SUB y -> l",
+            "x MUL y -> m",
+        ]);
+
+        let imported = Circuit::from_verilog(&circuit.to_verilog().unwrap().to_string()).unwrap();
+        assert_eq!(imported.instructions, circuit.instructions);
+    }
+
+    #[test]
+    fn import_hand_written_module() {
+        let circuit = Circuit::from_verilog(
+            "
+// Ports are declared in the body.
+module adder(a, b, sum);
+  input [15:0] a, b;
+  output [15:0] sum;
+  wire [15:0] \\or ; /* escaped, as it is a keyword */
+
+  assign \\or  = a ~^ 'hff;
+  assign sum = \\or  + b;
+endmodule
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            circuit.to_string(),
+            "0 -> a\n0 -> b\na XNOR 255 -> or\nor ADD b -> sum\n",
+        );
+
+        let signals = circuit
+            .emulate_with_overrides(&[("a", 0x0f), ("b", 1)])
+            .unwrap();
+        assert_eq!(signals["sum"], 0xff10);
+    }
+
+    #[test]
+    fn reject_unknown_function() {
+        let error = Circuit::from_verilog(
+            "module m;\n  function [15:0] f(input [15:0] x);\n    f = x;\n  endfunction\nendmodule",
+        )
+        .unwrap_err();
+
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 19);
+    }
+
+    #[test]
+    fn reject_rotation_with_another_body() {
+        let error = Circuit::from_verilog(
+            "\
+module m;
+  function [15:0] rotate_left(input [15:0] x, input [15:0] y);
+    rotate_left = x >> y % 16 | x << (16 - y % 16) % 16;
+  endfunction
+endmodule",
+        )
+        .unwrap_err();
+
+        assert_eq!(error.line(), 3);
+        assert_eq!(error.column(), 21);
+        assert_eq!(error.expected().collect::<Vec<_>>(), ["\"<<\""]);
+    }
+
+    #[test]
+    fn report_unsupported_operator() {
+        let error = Circuit::from_verilog("module m;\n  assign x = y / z;\nendmodule").unwrap_err();
+
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 16);
+        assert_eq!(error.expected().collect::<Vec<_>>(), ["\";\""]);
+    }
+
+    #[test]
+    fn reject_other_bus_widths() {
+        let error = Circuit::from_verilog("module m(input [7:0] x);\nendmodule").unwrap_err();
+
+        assert_eq!(error.column(), 17);
+        assert_eq!(error.expected().collect::<Vec<_>>(), ["\"15\""]);
+    }
+}
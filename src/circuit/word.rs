@@ -0,0 +1,74 @@
+use std::fmt;
+use std::hash::Hash;
+
+pub trait Word: Copy + Eq + Hash + fmt::Debug + fmt::Display {
+    /// From 1 to 64.
+    const BITS: u32;
+
+    /// `bits` has no bits set above the `BITS` lowest.
+    fn from_bits(bits: u64) -> Self;
+
+    fn to_bits(self) -> u64;
+}
+
+macro_rules! impl_word {
+    ($($ty:ty),*) => {
+        $(
+            impl Word for $ty {
+                const BITS: u32 = <$ty>::BITS;
+
+                fn from_bits(bits: u64) -> Self {
+                    bits as Self
+                }
+
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u8, u16, u32, u64);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Bits<const N: u32>(u64);
+
+impl<const N: u32> Bits<N> {
+    pub fn new(value: u64) -> Option<Self> {
+        if value <= mask(Self::BITS) {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const N: u32> Word for Bits<N> {
+    const BITS: u32 = {
+        assert!(N != 0 && N <= 64, "a bus must be from 1 to 64 bits wide");
+        N
+    };
+
+    fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    fn to_bits(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const N: u32> fmt::Display for Bits<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub(super) fn mask(bits: u32) -> u64 {
+    u64::MAX >> (64 - bits)
+}
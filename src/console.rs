@@ -1,15 +1,70 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::BufRead;
 use std::mem;
 use std::str::FromStr;
 
-use crate::parsing::{extract_digits, tag};
+use crate::parsing::{
+    all_consuming, alt, collect_all, read_lines, signed, tag, ParseError, ReadError,
+};
+
+mod debugger;
+mod repair;
+
+pub use debugger::{Breakpoint, Comparison, Debugger, Stop, TraceEntry};
+pub use repair::Repair;
 
 #[derive(Debug)]
 pub struct Vm {
     instructions: Vec<(Instruction, u32)>,
-    instruction_pointer: i32,
+    instruction_pointer: i64,
     accumulator: i32,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Registers {
+    pub ip: i64,
+    pub acc: i32,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ip {} acc {}", self.ip, self.acc)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The instruction pointer reached the address just past the end.
+    Halted {
+        acc: i32,
+    },
+    /// The instruction at `ip` was about to run a second time.
+    InfiniteLoop {
+        acc: i32,
+        ip: usize,
+    },
+    OutOfBounds {
+        ip: i64,
+    },
+    StepLimitExceeded,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Halted { acc } => write!(f, "halted with accumulator {}", acc),
+            Self::InfiniteLoop { acc, ip } => write!(
+                f,
+                "about to run instruction {} a second time, with accumulator {}",
+                ip, acc,
+            ),
+            Self::OutOfBounds { ip } => write!(f, "jumped out of bounds to {}", ip),
+            Self::StepLimitExceeded => write!(f, "ran out of steps"),
+        }
+    }
+}
+
 impl Vm {
     pub fn new(instructions: impl Iterator<Item = Instruction>) -> Self {
         Self {
@@ -19,80 +74,117 @@ impl Vm {
         }
     }
 
-    pub fn accumulator_after_fixing_program(&mut self) -> i32 {
-        let mut fix_attempt_idx = 0;
+    pub fn read(reader: impl BufRead) -> Result<Self, Vec<ReadError>> {
+        let instructions = collect_all(read_lines(reader, str::parse))?;
+        Ok(Self::new(instructions.into_iter()))
+    }
 
-        loop {
-            let new_operation = match self.instructions[fix_attempt_idx].0.operation {
-                Operation::Accumulate => {
-                    fix_attempt_idx += 1;
-                    continue;
-                }
-                Operation::Jump => Operation::NoOp,
-                Operation::NoOp => Operation::Jump,
-            };
+    pub fn accumulator_after_fixing_program(&self) -> Option<i32> {
+        self.repairs().first().map(|repair| repair.acc)
+    }
 
-            let old_operation = mem::replace(
-                &mut self.instructions[fix_attempt_idx].0.operation,
-                new_operation,
-            );
+    pub fn accumulator_before_loop(&mut self) -> Option<i32> {
+        match self.run() {
+            Outcome::InfiniteLoop { acc, .. } => Some(acc),
+            _ => None,
+        }
+    }
+
+    /// Always ends, since no instruction runs twice.
+    pub fn run(&mut self) -> Outcome {
+        self.run_for(usize::MAX)
+    }
+
+    pub fn run_for(&mut self, max_steps: usize) -> Outcome {
+        let mut steps = 0;
 
-            loop {
-                if self.will_loop() {
-                    break;
-                }
+        loop {
+            let ip = match self.next_address() {
+                Ok(ip) => ip,
+                Err(outcome) => return outcome,
+            };
 
-                if self.at_end() {
-                    return self.accumulator;
-                }
+            if self.instructions[ip].1 > 0 {
+                return Outcome::InfiniteLoop {
+                    acc: self.accumulator,
+                    ip,
+                };
             }
 
-            // Restore old operation before attempt.
-            let _ = mem::replace(
-                &mut self.instructions[fix_attempt_idx].0.operation,
-                old_operation,
-            );
+            if steps == max_steps {
+                return Outcome::StepLimitExceeded;
+            }
 
-            self.reset();
-            fix_attempt_idx += 1;
+            self.execute(ip);
+            steps += 1;
         }
     }
 
-    pub fn accumulator_before_loop(&mut self) -> i32 {
-        while !self.will_loop() {}
+    fn next_address(&self) -> Result<usize, Outcome> {
+        if self.instruction_pointer == self.instructions.len() as i64 {
+            return Err(Outcome::Halted {
+                acc: self.accumulator,
+            });
+        }
 
-        self.accumulator
+        match usize::try_from(self.instruction_pointer) {
+            Ok(ip) if ip < self.instructions.len() => Ok(ip),
+            _ => Err(Outcome::OutOfBounds {
+                ip: self.instruction_pointer,
+            }),
+        }
     }
 
-    fn will_loop(&mut self) -> bool {
+    /// `ip` must be in bounds.
+    fn execute(&mut self, ip: usize) {
         let (
             Instruction {
                 operation,
                 argument,
             },
             ref mut num_times_already_evaled,
-        ) = self.instructions[self.instruction_pointer as usize];
-
-        if *num_times_already_evaled > 0 {
-            return true;
-        }
+        ) = self.instructions[ip];
 
         match operation {
             Operation::Accumulate => {
-                self.accumulator += argument;
+                self.accumulator = self.accumulator.wrapping_add(argument);
                 self.instruction_pointer += 1;
             }
-            Operation::Jump => self.instruction_pointer += argument,
+            Operation::Jump => self.instruction_pointer += i64::from(argument),
             Operation::NoOp => self.instruction_pointer += 1,
         }
 
         *num_times_already_evaled += 1;
+    }
+
+    pub fn registers(&self) -> Registers {
+        Registers {
+            ip: self.instruction_pointer,
+            acc: self.accumulator,
+        }
+    }
+
+    pub fn instruction(&self, address: usize) -> Option<Instruction> {
+        self.instructions
+            .get(address)
+            .map(|&(instruction, _)| instruction)
+    }
+
+    pub fn patch(&mut self, address: usize, instruction: Instruction) -> Option<Instruction> {
+        let (old_instruction, _) = self.instructions.get_mut(address)?;
+        Some(mem::replace(old_instruction, instruction))
+    }
 
-        false
+    pub fn hit_count(&self, address: usize) -> Option<u32> {
+        self.instructions.get(address).map(|&(_, count)| count)
     }
 
-    fn at_end(&self) -> bool {
-        self.instruction_pointer == self.instructions.len() as i32
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
     }
 
     pub fn reset(&mut self) {
@@ -105,34 +197,44 @@ impl Vm {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Instruction {
     operation: Operation,
     argument: i32,
 }
 
-impl FromStr for Instruction {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Instruction {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
         let (s, operation) = Operation::new(s)?;
         let s = tag(" ", s)?;
 
-        let (s, sign) = tag("+", s)
-            .map(|s| (s, 1))
-            .or_else(|_| tag("-", s).map(|s| (s, -1)))?;
+        let (s, argument) = signed(s)?;
+
+        Ok((
+            s,
+            Self {
+                operation,
+                argument,
+            },
+        ))
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
 
-        let (_s, number) = extract_digits(s)?;
-        let number: i32 = number.parse().unwrap();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
+    }
+}
 
-        Ok(Self {
-            operation,
-            argument: sign * number,
-        })
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:+}", self.operation, self.argument)
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum Operation {
     Accumulate,
     Jump,
@@ -140,35 +242,81 @@ enum Operation {
 }
 
 impl Operation {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_accumulate(s)
-            .or_else(|_| Self::new_jump(s))
-            .or_else(|_| Self::new_no_op(s))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt((Self::new_accumulate, Self::new_jump, Self::new_no_op), s)
     }
 
-    fn new_accumulate(s: &str) -> Result<(&str, Self), String> {
+    fn new_accumulate(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("acc", s)?;
         Ok((s, Self::Accumulate))
     }
 
-    fn new_jump(s: &str) -> Result<(&str, Self), String> {
+    fn new_jump(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("jmp", s)?;
         Ok((s, Self::Jump))
     }
 
-    fn new_no_op(s: &str) -> Result<(&str, Self), String> {
+    fn new_no_op(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("nop", s)?;
         Ok((s, Self::NoOp))
     }
 }
 
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self {
+            Self::Accumulate => "acc",
+            Self::Jump => "jmp",
+            Self::NoOp => "nop",
+        };
+
+        write!(f, "{}", operation)
+    }
+}
+
+#[cfg(test)]
+mod parsing_tests {
+    use super::*;
+
+    #[test]
+    fn parse_negative_argument() {
+        let instruction: Instruction = "jmp -3".parse().unwrap();
+        assert_eq!(instruction.argument, -3);
+    }
+
+    #[test]
+    fn reject_argument_that_overflows() {
+        let error = "acc +2147483648".parse::<Instruction>().unwrap_err();
+
+        assert_eq!(error.column(), 5);
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            ["a number that fits in i32"],
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for instruction in &["nop +0", "acc -99", "jmp +4"] {
+            assert_eq!(
+                instruction.parse::<Instruction>().unwrap().to_string(),
+                *instruction,
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn vm(program: &str) -> Vm {
+        Vm::new(program.lines().map(|line| line.parse().unwrap()))
+    }
+
     #[test]
     fn it_works() {
-        let instructions = "nop +0
+        let mut vm = vm("nop +0
 acc +1
 jmp +4
 acc +3
@@ -176,14 +324,65 @@ jmp -3
 acc -99
 acc +1
 jmp -4
-acc +6"
-            .lines()
-            .map(|line| line.parse().unwrap());
+acc +6");
+
+        assert_eq!(vm.accumulator_before_loop(), Some(5));
+        vm.reset();
+        assert_eq!(vm.accumulator_after_fixing_program(), Some(8));
+    }
+
+    #[test]
+    fn halt_at_end() {
+        assert_eq!(
+            vm("acc +2\njmp +2\nacc +5\nacc -1").run(),
+            Outcome::Halted { acc: 1 },
+        );
+    }
+
+    #[test]
+    fn report_infinite_loop() {
+        assert_eq!(
+            vm("nop +0\nacc +3\njmp -1").run(),
+            Outcome::InfiniteLoop { acc: 3, ip: 1 },
+        );
+    }
+
+    #[test]
+    fn report_jumps_out_of_bounds() {
+        assert_eq!(vm("nop +0\njmp -2").run(), Outcome::OutOfBounds { ip: -1 });
+        assert_eq!(vm("jmp +3\nnop +0").run(), Outcome::OutOfBounds { ip: 3 });
+        assert_eq!(
+            vm("jmp +2147483647").run(),
+            Outcome::OutOfBounds { ip: 2147483647 },
+        );
+    }
+
+    #[test]
+    fn stop_at_step_limit() {
+        let mut vm = vm("nop +0\nacc +1\nacc +1");
 
-        let mut vm = Vm::new(instructions);
+        assert_eq!(vm.run_for(2), Outcome::StepLimitExceeded);
+        assert_eq!(vm.run_for(2), Outcome::Halted { acc: 2 });
+    }
+
+    #[test]
+    fn patch_instruction() {
+        let mut vm = vm("nop +0\nacc +1\njmp -2");
+
+        assert_eq!(
+            vm.patch(2, "acc +2".parse().unwrap()),
+            Some("jmp -2".parse().unwrap())
+        );
+        assert_eq!(vm.patch(3, "nop +0".parse().unwrap()), None);
+        assert_eq!(vm.run().to_string(), "halted with accumulator 3");
+    }
+
+    #[test]
+    fn no_fix_halts_program() {
+        let mut vm = vm("jmp +0\nacc +1\njmp -1");
 
-        // assert_eq!(vm.accumulator_before_loop(), 5);
-        // vm.reset();
-        assert_eq!(vm.accumulator_after_fixing_program(), 8);
+        assert_eq!(vm.accumulator_after_fixing_program(), None);
+        vm.reset();
+        assert_eq!(vm.accumulator_before_loop(), Some(0));
     }
 }
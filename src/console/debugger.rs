@@ -0,0 +1,403 @@
+use super::{Instruction, Outcome, Registers, Vm};
+use crate::parsing::{all_consuming, alt, preceded, signed, tag, unsigned, ParseError};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct Debugger {
+    vm: Vm,
+    breakpoints: Vec<Breakpoint>,
+    trace: Option<Vec<TraceEntry>>,
+    /// The address breakpoint `resume` last stopped at, if nothing has run since.
+    stopped_at: Option<usize>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stop {
+    Stepped,
+    /// Address breakpoints stop before their instruction runs, accumulator
+    /// breakpoints after.
+    Breakpoint(Breakpoint),
+    Ended(Outcome),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    Address(usize),
+    Accumulator(Comparison, i32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub address: usize,
+    pub instruction: Instruction,
+    pub registers: Registers,
+}
+
+impl Debugger {
+    pub fn new(vm: Vm) -> Self {
+        Self {
+            vm,
+            breakpoints: Vec::new(),
+            trace: None,
+            stopped_at: None,
+        }
+    }
+
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn vm(&self) -> &Vm {
+        &self.vm
+    }
+
+    pub fn vm_mut(&mut self) -> &mut Vm {
+        &mut self.vm
+    }
+
+    pub fn into_vm(self) -> Vm {
+        self.vm
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        if self.breakpoints.contains(&breakpoint) {
+            return false;
+        }
+
+        self.breakpoints.push(breakpoint);
+        true
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|&other| other != breakpoint);
+
+        self.breakpoints.len() < len
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn trace(&self) -> Option<&[TraceEntry]> {
+        self.trace.as_deref()
+    }
+
+    pub fn reset(&mut self) {
+        self.vm.reset();
+        self.stopped_at = None;
+
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+    }
+
+    /// Runs the next instruction, even one that has run before.
+    pub fn step(&mut self) -> Stop {
+        self.stopped_at = None;
+
+        let address = match self.vm.next_address() {
+            Ok(address) => address,
+            Err(outcome) => return Stop::Ended(outcome),
+        };
+
+        let acc_before = self.vm.accumulator;
+        self.vm.execute(address);
+        let registers = self.vm.registers();
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                address,
+                instruction: self.vm.instructions[address].0,
+                registers,
+            });
+        }
+
+        let became_true = self.breakpoints.iter().find(|breakpoint| match breakpoint {
+            Breakpoint::Accumulator(comparison, value) => {
+                !comparison.holds(acc_before, *value) && comparison.holds(registers.acc, *value)
+            }
+            Breakpoint::Address(_) => false,
+        });
+
+        match became_true {
+            Some(&breakpoint) => Stop::Breakpoint(breakpoint),
+            None => Stop::Stepped,
+        }
+    }
+
+    pub fn resume(&mut self) -> Stop {
+        let mut resuming_from = self.stopped_at.take();
+
+        loop {
+            let address = match self.vm.next_address() {
+                Ok(address) => address,
+                Err(outcome) => return Stop::Ended(outcome),
+            };
+
+            let is_resuming_from_address = resuming_from.take() == Some(address);
+
+            if !is_resuming_from_address && self.breakpoints.contains(&Breakpoint::Address(address))
+            {
+                self.stopped_at = Some(address);
+                return Stop::Breakpoint(Breakpoint::Address(address));
+            }
+
+            if self.vm.instructions[address].1 > 0 {
+                return Stop::Ended(Outcome::InfiniteLoop {
+                    acc: self.vm.accumulator,
+                    ip: address,
+                });
+            }
+
+            if let stop @ (Stop::Breakpoint(_) | Stop::Ended(_)) = self.step() {
+                return stop;
+            }
+        }
+    }
+}
+
+impl Breakpoint {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt((Self::new_address, Self::new_accumulator), s)
+    }
+
+    fn new_address(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, address) = unsigned(s)?;
+        Ok((s, Self::Address(address)))
+    }
+
+    fn new_accumulator(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, comparison) = preceded(|s| tag("acc ", s), Comparison::new, s)?;
+        let (s, value) = preceded(|s| tag(" ", s), signed, s)?;
+
+        Ok((s, Self::Accumulator(comparison, value)))
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{}", address),
+            Self::Accumulator(comparison, value) => write!(f, "acc {} {}", comparison, value),
+        }
+    }
+}
+
+impl Comparison {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                |s| tag("==", s).map(|s| (s, Self::Equal)),
+                |s| tag("!=", s).map(|s| (s, Self::NotEqual)),
+                |s| tag("<=", s).map(|s| (s, Self::LessOrEqual)),
+                |s| tag(">=", s).map(|s| (s, Self::GreaterOrEqual)),
+                |s| tag("<", s).map(|s| (s, Self::Less)),
+                |s| tag(">", s).map(|s| (s, Self::Greater)),
+            ),
+            s,
+        )
+    }
+
+    fn holds(&self, acc: i32, value: i32) -> bool {
+        match self {
+            Self::Equal => acc == value,
+            Self::NotEqual => acc != value,
+            Self::Less => acc < value,
+            Self::LessOrEqual => acc <= value,
+            Self::Greater => acc > value,
+            Self::GreaterOrEqual => acc >= value,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparison = match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+        };
+
+        write!(f, "{}", comparison)
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.address, self.instruction, self.registers,
+        )
+    }
+}
+
+#[cfg(test)]
+mod debugger_tests {
+    use super::*;
+    use std::iter;
+
+    fn debugger() -> Debugger {
+        let instructions = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6"
+            .lines()
+            .map(|line| line.parse().unwrap());
+
+        Debugger::new(Vm::new(instructions))
+    }
+
+    #[test]
+    fn step_through_program() {
+        let mut debugger = debugger();
+
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.vm().registers(), Registers { ip: 2, acc: 1 });
+        assert_eq!(debugger.vm().hit_count(1), Some(1));
+        assert_eq!(debugger.vm().hit_count(2), Some(0));
+        assert_eq!(debugger.vm().hit_count(9), None);
+    }
+
+    #[test]
+    fn stop_at_address_breakpoint() {
+        let mut debugger = debugger();
+        assert!(debugger.add_breakpoint(Breakpoint::Address(4)));
+        assert!(!debugger.add_breakpoint(Breakpoint::Address(4)));
+
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(4)));
+        assert_eq!(debugger.vm().registers(), Registers { ip: 4, acc: 5 });
+
+        assert_eq!(
+            debugger.resume(),
+            Stop::Ended(Outcome::InfiniteLoop { acc: 5, ip: 1 }),
+        );
+    }
+
+    #[test]
+    fn stop_at_breakpoint_on_entry_address() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(Breakpoint::Address(0));
+
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(0)));
+        assert_eq!(debugger.vm().registers(), Registers { ip: 0, acc: 0 });
+
+        assert_eq!(
+            debugger.resume(),
+            Stop::Ended(Outcome::InfiniteLoop { acc: 5, ip: 1 }),
+        );
+
+        debugger.reset();
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(0)));
+    }
+
+    #[test]
+    fn stop_at_breakpoint_on_next_address_after_stepping() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(Breakpoint::Address(2));
+
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(2)));
+        assert_eq!(debugger.vm().registers(), Registers { ip: 2, acc: 1 });
+    }
+
+    #[test]
+    fn stop_when_accumulator_condition_becomes_true() {
+        let mut debugger = debugger();
+        let breakpoint = Breakpoint::Accumulator(Comparison::GreaterOrEqual, 2);
+        debugger.add_breakpoint(breakpoint);
+
+        assert_eq!(debugger.resume(), Stop::Breakpoint(breakpoint));
+        assert_eq!(debugger.vm().registers(), Registers { ip: 7, acc: 2 });
+
+        assert_eq!(
+            debugger.resume(),
+            Stop::Ended(Outcome::InfiniteLoop { acc: 5, ip: 1 }),
+        );
+    }
+
+    #[test]
+    fn report_end_of_program() {
+        let mut debugger = Debugger::new(Vm::new(iter::once("acc +7".parse().unwrap())));
+
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.step(), Stop::Ended(Outcome::Halted { acc: 7 }));
+        assert_eq!(debugger.resume(), Stop::Ended(Outcome::Halted { acc: 7 }));
+    }
+
+    #[test]
+    fn trace_execution() {
+        let mut debugger = debugger().with_trace();
+        debugger.resume();
+
+        let trace: Vec<_> = debugger
+            .trace()
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            trace,
+            [
+                "0: nop +0 -> ip 1 acc 0",
+                "1: acc +1 -> ip 2 acc 1",
+                "2: jmp +4 -> ip 6 acc 1",
+                "6: acc +1 -> ip 7 acc 2",
+                "7: jmp -4 -> ip 3 acc 2",
+                "3: acc +3 -> ip 4 acc 5",
+                "4: jmp -3 -> ip 1 acc 5",
+            ],
+        );
+
+        debugger.reset();
+        assert_eq!(debugger.trace(), Some(&[][..]));
+    }
+
+    #[test]
+    fn parse_breakpoints() {
+        for breakpoint in &["12", "acc == 0", "acc != -3", "acc < 4", "acc >= 10"] {
+            assert_eq!(
+                breakpoint.parse::<Breakpoint>().unwrap().to_string(),
+                *breakpoint,
+            );
+        }
+
+        assert_eq!(
+            "acc > -1".parse(),
+            Ok(Breakpoint::Accumulator(Comparison::Greater, -1)),
+        );
+        assert!("acc = 1".parse::<Breakpoint>().is_err());
+    }
+}
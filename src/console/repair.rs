@@ -0,0 +1,230 @@
+use super::{Instruction, Operation, Vm};
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Repair {
+    pub address: usize,
+    pub instruction: Instruction,
+    pub acc: i32,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at address {} halts with accumulator {}",
+            self.instruction, self.address, self.acc,
+        )
+    }
+}
+
+impl Vm {
+    /// Linear time. The instructions that reach the end form a tree rooted at
+    /// the end, and a swap works if the new successor is in that tree but not
+    /// below the swapped instruction.
+    pub fn repairs(&self) -> Vec<Repair> {
+        let len = self.instructions.len();
+        let mut predecessors = vec![Vec::new(); len + 1];
+
+        for (address, (instruction, _)) in self.instructions.iter().enumerate() {
+            if let Some(successor) = instruction.successor(address, len) {
+                predecessors[successor].push(address);
+            }
+        }
+
+        // The accumulator gained on the way to the end, and when a depth-first
+        // search from the end entered and left each instruction, for every
+        // instruction that reaches the end.
+        let mut to_end: Vec<Option<(i32, usize, usize)>> = vec![None; len + 1];
+        let mut clock = 0;
+        let mut stack = vec![(len, 0)];
+        to_end[len] = Some((0, clock, 0));
+
+        while let Some(&(address, child)) = stack.last() {
+            let (acc, _, _) = to_end[address].unwrap();
+
+            match predecessors[address].get(child) {
+                Some(&predecessor) => {
+                    stack.last_mut().unwrap().1 += 1;
+                    clock += 1;
+
+                    let (instruction, _) = self.instructions[predecessor];
+                    to_end[predecessor] =
+                        Some((acc.wrapping_add(instruction.acc_delta()), clock, 0));
+                    stack.push((predecessor, 0));
+                }
+                None => {
+                    clock += 1;
+                    to_end[address].as_mut().unwrap().2 = clock;
+                    stack.pop();
+                }
+            }
+        }
+
+        // Whether `ancestor` is on the way from `address` to the end.
+        let is_on_way_to_end =
+            |ancestor: usize, address: usize| match (to_end[ancestor], to_end[address]) {
+                (Some((_, entered, left)), Some((_, address_entered, address_left))) => {
+                    entered <= address_entered && address_left <= left
+                }
+                _ => false,
+            };
+
+        let mut repairs = Vec::new();
+        let mut is_visited = vec![false; len];
+        let mut address = 0;
+        let mut acc = 0_i32;
+
+        while address < len && !is_visited[address] {
+            is_visited[address] = true;
+            let (instruction, _) = self.instructions[address];
+
+            if let Some(swapped) = instruction.swapped() {
+                let repair = swapped
+                    .successor(address, len)
+                    .filter(|&successor| !is_on_way_to_end(address, successor))
+                    .and_then(|successor| to_end[successor]);
+
+                if let Some((acc_to_end, _, _)) = repair {
+                    repairs.push(Repair {
+                        address,
+                        instruction: swapped,
+                        acc: acc.wrapping_add(acc_to_end),
+                    });
+                }
+            }
+
+            acc = acc.wrapping_add(instruction.acc_delta());
+
+            match instruction.successor(address, len) {
+                Some(successor) => address = successor,
+                None => break,
+            }
+        }
+
+        repairs
+    }
+}
+
+impl Instruction {
+    /// `None` unless within the program or just past its end.
+    fn successor(&self, address: usize, len: usize) -> Option<usize> {
+        let offset = match self.operation {
+            Operation::Jump => i64::from(self.argument),
+            Operation::Accumulate | Operation::NoOp => 1,
+        };
+
+        usize::try_from(address as i64 + offset)
+            .ok()
+            .filter(|&successor| successor <= len)
+    }
+
+    fn acc_delta(&self) -> i32 {
+        match self.operation {
+            Operation::Accumulate => self.argument,
+            Operation::Jump | Operation::NoOp => 0,
+        }
+    }
+
+    fn swapped(&self) -> Option<Self> {
+        let operation = match self.operation {
+            Operation::Accumulate => return None,
+            Operation::Jump => Operation::NoOp,
+            Operation::NoOp => Operation::Jump,
+        };
+
+        Some(Self { operation, ..*self })
+    }
+}
+
+#[cfg(test)]
+mod repair_tests {
+    use super::super::Outcome;
+    use super::*;
+
+    fn vm(program: &str) -> Vm {
+        Vm::new(program.lines().map(|line| line.parse().unwrap()))
+    }
+
+    fn brute_force_repairs(vm: &Vm) -> Vec<Repair> {
+        let mut repairs = Vec::new();
+
+        for (address, (instruction, _)) in vm.instructions.iter().enumerate() {
+            let swapped = match instruction.swapped() {
+                Some(swapped) => swapped,
+                None => continue,
+            };
+
+            let mut patched = Vm::new(vm.instructions.iter().map(|&(instruction, _)| instruction));
+            patched.instructions[address].0 = swapped;
+
+            if let Outcome::Halted { acc } = patched.run() {
+                repairs.push(Repair {
+                    address,
+                    instruction: swapped,
+                    acc,
+                });
+            }
+        }
+
+        repairs
+    }
+
+    #[test]
+    fn repair_example() {
+        let vm = vm("nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6");
+
+        assert_eq!(
+            vm.repairs(),
+            [Repair {
+                address: 7,
+                instruction: "nop -4".parse().unwrap(),
+                acc: 8,
+            }],
+        );
+        assert_eq!(
+            vm.repairs()[0].to_string(),
+            "nop -4 at address 7 halts with accumulator 8"
+        );
+    }
+
+    #[test]
+    fn list_every_repair() {
+        let vm = vm("nop +3\nacc +1\njmp -2\nacc +10");
+        let repairs = vm.repairs();
+
+        assert_eq!(repairs, brute_force_repairs(&vm));
+        assert_eq!(
+            repairs
+                .iter()
+                .map(|repair| (repair.address, repair.acc))
+                .collect::<Vec<_>>(),
+            [(0, 10), (2, 11)],
+        );
+    }
+
+    #[test]
+    fn reject_repair_that_runs_itself_again() {
+        // The program already halts, but swapping either instruction it runs
+        // leads back through that instruction.
+        let mut vm = vm("nop +2\njmp +2\njmp -2");
+
+        assert_eq!(vm.run(), Outcome::Halted { acc: 0 });
+        assert_eq!(vm.repairs(), []);
+    }
+
+    #[test]
+    fn repair_puzzle_input() {
+        let vm = Vm::read(include_str!("../bin/console_input").as_bytes()).unwrap();
+        assert_eq!(vm.repairs(), brute_force_repairs(&vm));
+    }
+}
@@ -1,8 +1,15 @@
+use crate::parsing::{lines, read_groups, ReadError};
+use std::io::BufRead;
+
 pub struct Group {
     person_questions: Vec<PersonQuestions>,
 }
 
 impl Group {
+    pub fn read_all(reader: impl BufRead) -> impl Iterator<Item = Result<Self, ReadError>> {
+        read_groups(reader, |group| Ok(Self::from(group)))
+    }
+
     pub fn num_questions(&self) -> usize {
         self.unique_questions().len()
     }
@@ -36,7 +43,7 @@ impl Group {
 impl From<&str> for Group {
     fn from(s: &str) -> Self {
         Self {
-            person_questions: s.lines().map(PersonQuestions::from).collect(),
+            person_questions: lines(s).map(PersonQuestions::from).collect(),
         }
     }
 }
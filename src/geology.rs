@@ -1,6 +1,7 @@
-use crate::parsing::tag;
+use crate::parsing::{all_consuming, alt, lines, parse_lines, tag, ParseError};
 use std::str::FromStr;
 
+#[derive(Debug)]
 pub struct Map {
     data: Vec<Datum>,
     width: usize,
@@ -34,37 +35,39 @@ impl Map {
 }
 
 impl FromStr for Map {
-    type Err = String;
+    type Err = ParseError;
 
-    fn from_str(mut s: &str) -> Result<Self, Self::Err> {
-        let width = s
-            .lines()
-            .next()
-            .ok_or_else(|| "expected at least one line".to_string())?
-            .len();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Every row has to be as wide as the first.
+        let width = lines(s).next().map_or(0, |line| line.chars().count());
 
-        let height = s.lines().count();
+        if width == 0 {
+            return Err(ParseError::new(s, "a row with at least one square").locate(s));
+        }
 
-        let mut data = Vec::new();
+        let rows = parse_lines(s, |line| all_consuming(|s| Self::new_row(s, width), line))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        loop {
-            s = s.strip_prefix("\n").unwrap_or(s);
+        Ok(Self {
+            height: rows.len(),
+            width,
+            data: rows.concat(),
+        })
+    }
+}
 
-            if s.is_empty() {
-                break;
-            }
+impl Map {
+    fn new_row(mut s: &str, width: usize) -> Result<(&str, Vec<Datum>), ParseError> {
+        let mut row = Vec::with_capacity(width);
 
+        for _ in 0..width {
             let (new_s, datum) = Datum::new(s)?;
             s = new_s;
 
-            data.push(datum);
+            row.push(datum);
         }
 
-        Ok(Self {
-            data,
-            width,
-            height,
-        })
+        Ok((s, row))
     }
 }
 
@@ -85,17 +88,21 @@ impl Iterator for PathIter<'_> {
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum Datum {
     Open,
     Tree,
 }
 
 impl Datum {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        tag(".", s)
-            .map(|s| (s, Self::Open))
-            .or_else(|_| tag("#", s).map(|s| (s, Self::Tree)))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt(
+            (
+                |s| tag(".", s).map(|s| (s, Self::Open)),
+                |s| tag("#", s).map(|s| (s, Self::Tree)),
+            ),
+            s,
+        )
     }
 }
 
@@ -117,3 +124,41 @@ pub struct Slope {
     pub right: usize,
     pub down: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#
+";
+
+    #[test]
+    fn count_trees() {
+        let map = Map::from_str(MAP).unwrap();
+        assert_eq!(map.num_trees_in_path(Slope { right: 3, down: 1 }), 7);
+    }
+
+    #[test]
+    fn reject_ragged_rows() {
+        let error = Map::from_str("..#\n.#\n").unwrap_err();
+
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 3);
+    }
+
+    #[test]
+    fn reject_empty_map() {
+        assert!(Map::from_str("").is_err());
+        assert!(Map::from_str("\n#").is_err());
+    }
+}
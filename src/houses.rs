@@ -1,12 +1,21 @@
+use crate::parsing::lines;
+
 pub struct Moves {
     moves: Vec<Move>,
 }
 
 impl Moves {
     pub fn new(s: &str) -> Option<Self> {
+        let mut lines = lines(s);
+        let line = lines.next().unwrap_or("");
+
+        if lines.next().is_some() {
+            return None;
+        }
+
         let mut moves = Vec::new();
 
-        for c in s.chars() {
+        for c in line.chars() {
             moves.push(match c {
                 '^' => Move::Up,
                 'v' => Move::Down,
@@ -127,6 +136,17 @@ mod with_robosanta_tests {
     fn up_down_up_down_up_down() {
         check("^v^v^v", 7);
     }
+
+    #[test]
+    fn trailing_line_ending() {
+        check("^v\n", 3);
+        check("^v\r\n", 3);
+    }
+
+    #[test]
+    fn reject_more_than_one_line() {
+        assert!(Moves::new("^v\n^v").is_none());
+    }
 }
 
 #[cfg(test)]
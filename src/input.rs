@@ -0,0 +1,38 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::{env, fmt, process};
+
+pub struct Source {
+    pub name: String,
+    pub reader: Box<dyn BufRead>,
+}
+
+impl Source {
+    /// `-` means standard input. Without an argument, `default` is used.
+    pub fn from_args(default_name: &str, default: &'static str) -> io::Result<Self> {
+        let source = match env::args_os().nth(1) {
+            Some(path) if path == "-" => Self {
+                name: "<stdin>".to_string(),
+                reader: Box::new(BufReader::new(io::stdin())),
+            },
+            Some(path) => Self {
+                name: path.to_string_lossy().into_owned(),
+                reader: Box::new(BufReader::new(File::open(&path)?)),
+            },
+            None => Self {
+                name: default_name.to_string(),
+                reader: Box::new(default.as_bytes()),
+            },
+        };
+
+        Ok(source)
+    }
+}
+
+pub fn exit_with_errors<D: fmt::Display>(diagnostics: impl IntoIterator<Item = D>) -> ! {
+    for diagnostic in diagnostics {
+        eprintln!("{}\n", diagnostic);
+    }
+
+    process::exit(1);
+}
@@ -8,10 +8,11 @@ pub mod customs;
 pub mod expense_report;
 pub mod geology;
 pub mod houses;
+pub mod input;
 pub mod light_grid;
 pub mod luggage;
 pub mod naughty_or_nice_strings;
-mod parsing;
+pub mod parsing;
 pub mod passport;
 pub mod password;
 pub mod presents;
@@ -1,6 +1,10 @@
+use std::fmt;
+use std::io::BufRead;
 use std::str::FromStr;
 
-use crate::parsing::{extract_digits, tag};
+use crate::parsing::{
+    all_consuming, alt, in_range, read_lines, tag, template, unsigned, Field, ParseError, ReadError,
+};
 
 const ROWS: usize = 1000;
 const COLUMNS: usize = 1000;
@@ -39,31 +43,46 @@ impl<L: Light> Grid<L> {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Instruction {
     action: Action,
     from: Coordinate,
     to: Coordinate,
 }
 
-impl FromStr for Instruction {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (s, action) = Action::new(s)?;
-        let s = tag(" ", s)?;
+impl Instruction {
+    pub fn read_all(reader: impl BufRead) -> impl Iterator<Item = Result<Self, ReadError>> {
+        read_lines(reader, str::parse)
+    }
 
-        let (s, from) = Coordinate::new(s)?;
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        // `to` is parsed separately so that we can point at it if it comes
+        // before `from`.
+        let (s, (action, from)): (_, (_, Coordinate)) = template("{action} {from} through ", s)?;
+        let (new_s, to) = Coordinate::new(s)?;
+
+        if to.x < from.x || to.y < from.y {
+            return Err(ParseError::new(
+                s,
+                format!("a coordinate no smaller than {},{}", from.x, from.y),
+            ));
+        }
 
-        let s = tag(" through ", s)?;
+        Ok((new_s, Self { action, from, to }))
+    }
+}
 
-        let (s, to) = Coordinate::new(s)?;
+impl FromStr for Instruction {
+    type Err = ParseError;
 
-        if !s.is_empty() {
-            return Err("parser did not consume entire input".to_string());
-        }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
+    }
+}
 
-        Ok(Self { action, from, to })
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} through {}", self.action, self.from, self.to)
     }
 }
 
@@ -75,36 +94,53 @@ pub enum Action {
 }
 
 impl Action {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_turn_on(s)
-            .or_else(|_| Self::new_turn_off(s))
-            .or_else(|_| Self::new_toggle(s))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt((Self::new_turn_on, Self::new_turn_off, Self::new_toggle), s)
     }
 
-    fn new_turn_on(s: &str) -> Result<(&str, Self), String> {
+    fn new_turn_on(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("turn on", s)?;
         Ok((s, Self::TurnOn))
     }
 
-    fn new_turn_off(s: &str) -> Result<(&str, Self), String> {
+    fn new_turn_off(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("turn off", s)?;
         Ok((s, Self::TurnOff))
     }
 
-    fn new_toggle(s: &str) -> Result<(&str, Self), String> {
+    fn new_toggle(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("toggle", s)?;
         Ok((s, Self::Toggle))
     }
 }
 
+impl Field<'_> for Action {
+    fn parse_field(s: &str) -> Result<(&str, Self), ParseError> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            Self::TurnOn => "turn on",
+            Self::TurnOff => "turn off",
+            Self::Toggle => "toggle",
+        };
+
+        write!(f, "{}", action)
+    }
+}
+
 pub trait Light: Clone + Default {
     fn apply(&mut self, action: Action);
     fn brightness(&self) -> u32;
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Default, PartialEq)]
 pub enum BinaryLight {
     On,
+    #[default]
     Off,
 }
 
@@ -128,13 +164,7 @@ impl Light for BinaryLight {
     }
 }
 
-impl Default for BinaryLight {
-    fn default() -> Self {
-        Self::Off
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct ScalarLight {
     brightness: u32,
 }
@@ -153,12 +183,6 @@ impl Light for ScalarLight {
     }
 }
 
-impl Default for ScalarLight {
-    fn default() -> Self {
-        Self { brightness: 0 }
-    }
-}
-
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct Coordinate {
     x: usize,
@@ -166,18 +190,27 @@ struct Coordinate {
 }
 
 impl Coordinate {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, x) = extract_digits(s)?;
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, x) = in_range(unsigned, 0..COLUMNS, s)?;
         let s = tag(",", s)?;
-        let (s, y) = extract_digits(s)?;
-
-        let x = x.parse().unwrap();
-        let y = y.parse().unwrap();
+        let (s, y) = in_range(unsigned, 0..ROWS, s)?;
 
         Ok((s, Self { x, y }))
     }
 }
 
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl Field<'_> for Coordinate {
+    fn parse_field(s: &str) -> Result<(&str, Self), ParseError> {
+        Self::new(s)
+    }
+}
+
 #[cfg(test)]
 mod binary_tests {
     use super::*;
@@ -297,4 +330,40 @@ mod parsing_tests {
             Ok(("", Coordinate { x: 123, y: 456 })),
         );
     }
+
+    #[test]
+    fn reject_coordinates_in_wrong_order() {
+        let error = "turn off 5,5 through 4,9"
+            .parse::<Instruction>()
+            .unwrap_err();
+
+        assert_eq!(error.column(), 22);
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            ["a coordinate no smaller than 5,5"],
+        );
+    }
+
+    #[test]
+    fn reject_coordinate_outside_grid() {
+        let error = "toggle 0,0 through 1000,5"
+            .parse::<Instruction>()
+            .unwrap_err();
+
+        assert_eq!(error.column(), 20);
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            ["a number in 0..1000"]
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let instruction = "turn off 499,499 through 500,500";
+
+        assert_eq!(
+            instruction.parse::<Instruction>().unwrap().to_string(),
+            instruction,
+        );
+    }
 }
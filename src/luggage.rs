@@ -1,62 +1,143 @@
-use crate::parsing::{extract_digits, extract_lowercase, sequence1, tag};
-use std::collections::HashMap;
+use crate::parsing::{
+    all_consuming, alt, extract_lowercase, opt_tag, parse_lines, separated_list1, tag, unsigned,
+    ParseError,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Rules {
     rules: HashMap<Bag, Specification>,
 }
 
 impl Rules {
     pub fn num_bags_that_can_contain(&self, bag: &Bag) -> usize {
-        fn can_contain(rules: &Rules, bag: &Bag, spec: &Specification) -> bool {
-            let bags_with_quantities = match spec {
-                Specification::MultipleBags(bags_with_quantities) => bags_with_quantities,
-                Specification::NoBags => return false,
-            };
-
-            bags_with_quantities
-                .iter()
-                .any(|BagWithQuantity { bag: spec_bag, .. }| {
-                    spec_bag == bag || can_contain(rules, bag, rules.rules.get(spec_bag).unwrap())
-                })
+        let mut containers: HashMap<&Bag, Vec<&Bag>> = HashMap::new();
+
+        for (subject, spec) in &self.rules {
+            if let Specification::MultipleBags(bags_with_quantities) = spec {
+                for BagWithQuantity { bag, .. } in bags_with_quantities {
+                    containers.entry(bag).or_default().push(subject);
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut to_visit = vec![bag];
+
+        while let Some(bag) = to_visit.pop() {
+            for &container in containers.get(bag).into_iter().flatten() {
+                if seen.insert(container) {
+                    to_visit.push(container);
+                }
+            }
         }
 
-        self.rules
-            .values()
-            .filter(|contains| can_contain(self, bag, contains))
-            .count()
+        seen.len()
+    }
+
+    pub fn num_bags_contained_by(&self, bag: &Bag) -> Result<usize, RulesError> {
+        let mut counts: HashMap<&Bag, usize> = HashMap::new();
+
+        // The bags being counted, each with how many of its contents have
+        // been visited.
+        let mut open = HashSet::new();
+        let mut stack = vec![(bag, 0)];
+        open.insert(bag);
+
+        while let Some(&mut (bag, ref mut visited)) = stack.last_mut() {
+            let contents = self.contents(bag);
+
+            if let Some(BagWithQuantity { bag: inner, .. }) = contents.get(*visited) {
+                *visited += 1;
+
+                if counts.contains_key(inner) {
+                    continue;
+                }
+
+                if !open.insert(inner) {
+                    return Err(RulesError::Cycle(inner.clone()));
+                }
+
+                stack.push((inner, 0));
+                continue;
+            }
+
+            let mut count = 0_usize;
+
+            for BagWithQuantity { bag, quantity } in contents {
+                count = counts[bag]
+                    .checked_add(1)
+                    .and_then(|bags| bags.checked_mul(*quantity))
+                    .and_then(|bags| bags.checked_add(count))
+                    .ok_or(RulesError::TooManyBags)?;
+            }
+
+            stack.pop();
+            open.remove(bag);
+            counts.insert(bag, count);
+        }
+
+        Ok(counts[bag])
+    }
+
+    /// A bag without a rule contains nothing.
+    fn contents(&self, bag: &Bag) -> &[BagWithQuantity] {
+        match self.rules.get(bag) {
+            Some(Specification::MultipleBags(bags_with_quantities)) => bags_with_quantities,
+            Some(Specification::NoBags) | None => &[],
+        }
     }
+}
 
-    pub fn num_bags_contained_by(&self, bag: &Bag) -> usize {
-        match self.rules.get(bag).unwrap() {
-            Specification::MultipleBags(bags_with_quantities) => bags_with_quantities
-                .iter()
-                .map(|BagWithQuantity { bag, quantity }| {
-                    quantity + self.num_bags_contained_by(bag) * quantity
-                })
-                .sum(),
+#[derive(Debug, PartialEq)]
+pub enum RulesError {
+    Cycle(Bag),
+    TooManyBags,
+}
 
-            Specification::NoBags => 0,
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle(bag) => write!(f, "{} bags end up inside themselves", bag),
+            Self::TooManyBags => write!(f, "there are too many bags to count"),
         }
     }
 }
 
+impl std::error::Error for RulesError {}
+
 impl FromStr for Rules {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (_s, rules) = sequence1(Rule::new, |s| tag("\n", s), s)?;
-
-        let rules = rules
-            .into_iter()
-            .map(|Rule { subject, spec }| (subject, spec))
-            .collect();
+        // Parsing each line on its own means that an error points at the rule
+        // that could not be parsed, rather than at the end of the last rule
+        // that could.
+        let rules = parse_lines(s, |line| {
+            all_consuming(Rule::new, line).map(|Rule { subject, spec }| (subject, spec))
+        })
+        .collect::<Result<_, _>>()?;
 
         Ok(Self { rules })
     }
 }
 
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Sorted so that the same rules are always written out the same way.
+        let mut rules: Vec<_> = self.rules.iter().collect();
+        rules.sort_unstable_by_key(|(bag, _)| (&bag.modifier, &bag.color));
+
+        for (subject, spec) in rules {
+            writeln!(f, "{} bags contain {}.", subject, spec)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct Rule {
     subject: Bag,
@@ -64,7 +145,7 @@ struct Rule {
 }
 
 impl Rule {
-    fn new(s: &str) -> Result<(&str, Self), String> {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
         let (s, subject) = Bag::new(s)?;
         let s = tag(" bags contain ", s)?;
 
@@ -83,22 +164,41 @@ enum Specification {
 }
 
 impl Specification {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_multiple(s).or_else(|_| Self::new_no_bags(s))
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        alt((Self::new_multiple, Self::new_no_bags), s)
     }
 
-    fn new_multiple(s: &str) -> Result<(&str, Self), String> {
-        sequence1(BagWithQuantity::new, |s| tag(", ", s), s)
+    fn new_multiple(s: &str) -> Result<(&str, Self), ParseError> {
+        separated_list1(BagWithQuantity::new, |s| tag(", ", s), s)
             .map(|(s, bags_with_quantities)| (s, Self::MultipleBags(bags_with_quantities)))
     }
 
-    fn new_no_bags(s: &str) -> Result<(&str, Self), String> {
+    fn new_no_bags(s: &str) -> Result<(&str, Self), ParseError> {
         let s = tag("no other bags", s)?;
 
         Ok((s, Self::NoBags))
     }
 }
 
+impl fmt::Display for Specification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MultipleBags(bags_with_quantities) => {
+                for (idx, bag_with_quantity) in bags_with_quantities.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{}", bag_with_quantity)?;
+                }
+
+                Ok(())
+            }
+            Self::NoBags => write!(f, "no other bags"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct BagWithQuantity {
     bag: Bag,
@@ -106,29 +206,34 @@ struct BagWithQuantity {
 }
 
 impl BagWithQuantity {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, num_bags) = extract_digits(s)?;
-        let quantity = num_bags.parse().unwrap();
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, quantity) = unsigned(s)?;
         let s = tag(" ", s)?;
 
         let (s, bag) = Bag::new(s)?;
 
         let s = tag(" bag", s)?;
-        // Handle plural.
-        let s = tag("s", s).unwrap_or(s);
+        let (s, _plural) = opt_tag("s", s);
 
         Ok((s, Self { bag, quantity }))
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+impl fmt::Display for BagWithQuantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.quantity == 1 { "" } else { "s" };
+        write!(f, "{} {} bag{}", self.quantity, self.bag, plural)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Bag {
     pub modifier: String,
     pub color: String,
 }
 
 impl Bag {
-    fn new(s: &str) -> Result<(&str, Self), String> {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
         let (s, modifier) = extract_lowercase(s)?;
         let s = tag(" ", s)?;
         let (s, color) = extract_lowercase(s)?;
@@ -143,6 +248,12 @@ impl Bag {
     }
 }
 
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.modifier, self.color)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,4 +318,96 @@ dark green bags contain 1 shiny gold bag.",
 
         assert_eq!(rules.num_bags_that_can_contain(&bag), 2);
     }
+
+    #[test]
+    fn count_bags_inside() {
+        let rules = Rules::from_str(
+            "shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain no other bags.",
+        )
+        .unwrap();
+
+        assert_eq!(rules.num_bags_contained_by(&shiny_gold()), Ok(6));
+    }
+
+    #[test]
+    fn handle_rules_that_go_round_in_a_circle() {
+        let rules = Rules::from_str(
+            "shiny gold bags contain 1 dark red bag.
+dark red bags contain 2 shiny gold bags.",
+        )
+        .unwrap();
+
+        assert_eq!(rules.num_bags_that_can_contain(&shiny_gold()), 2);
+        assert!(matches!(
+            rules.num_bags_contained_by(&shiny_gold()),
+            Err(RulesError::Cycle(_)),
+        ));
+    }
+
+    #[test]
+    fn count_bags_in_deep_chain() {
+        const DEPTH: usize = 200_000;
+
+        let modifier = |mut idx: usize| {
+            let mut modifier = String::new();
+
+            loop {
+                modifier.push((b'a' + (idx % 26) as u8) as char);
+                idx /= 26;
+
+                if idx == 0 {
+                    return modifier;
+                }
+            }
+        };
+
+        let mut rules = String::from("shiny gold bags contain 1 a dark bag.\n");
+
+        for idx in 0..DEPTH {
+            rules += &format!(
+                "{} dark bags contain 1 {} dark bag.\n",
+                modifier(idx),
+                modifier(idx + 1),
+            );
+        }
+
+        let rules = Rules::from_str(&rules).unwrap();
+
+        assert_eq!(rules.num_bags_contained_by(&shiny_gold()), Ok(DEPTH + 1));
+    }
+
+    #[test]
+    fn report_too_many_bags() {
+        let rules = Rules::from_str(
+            "shiny gold bags contain 4294967295 dark red bags.
+dark red bags contain 4294967295 dark orange bags.
+dark orange bags contain 4294967295 dark blue bags.
+dark blue bags contain 4294967295 dark green bags.",
+        )
+        .unwrap();
+
+        assert_eq!(
+            rules.num_bags_contained_by(&shiny_gold()),
+            Err(RulesError::TooManyBags),
+        );
+    }
+
+    fn shiny_gold() -> Bag {
+        Bag {
+            modifier: "shiny".to_string(),
+            color: "gold".to_string(),
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let rules = "bright white bags contain 1 shiny gold bag.
+faded blue bags contain no other bags.
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+";
+
+        assert_eq!(Rules::from_str(rules).unwrap().to_string(), rules);
+    }
 }
@@ -1,73 +1,800 @@
-pub(crate) fn sequence<T>(
-    parser: impl Fn(&str) -> Result<(&str, T), String>,
-    separator_parser: impl Fn(&str) -> Result<&str, String>,
-    mut s: &str,
-) -> Result<(&str, Vec<T>), String> {
-    let mut items = Vec::new();
-
-    while let Ok((new_s, item)) = parser(s) {
-        s = new_s;
-        items.push(item);
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::iter;
+use std::ops::RangeBounds;
+use std::str::FromStr;
+use std::vec;
+
+/// Only knows how much input was left when it was made, until it is located
+/// with [`ParseError::locate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    remaining: usize,
+    offset: usize,
+    line: usize,
+    column: usize,
+    expected: Vec<String>,
+}
+
+impl ParseError {
+    pub fn new(s: &str, expected: impl Into<String>) -> Self {
+        Self {
+            remaining: s.len(),
+            offset: 0,
+            line: 1,
+            column: 1,
+            expected: vec![expected.into()],
+        }
+    }
+
+    /// `source` must be the text given to the outermost parser.
+    pub fn locate(self, source: &str) -> Self {
+        let end = source.len();
+        self.locate_before(source, end)
+    }
+
+    fn locate_before(mut self, source: &str, end: usize) -> Self {
+        self.offset = end.saturating_sub(self.remaining);
+
+        let before = source.get(..self.offset).unwrap_or(source);
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+        self.line = before.matches('\n').count() + 1;
+        self.column = before[line_start..].chars().count() + 1;
+
+        self
+    }
+
+    fn shift(mut self, lines: usize, bytes: usize) -> Self {
+        self.line += lines;
+        self.offset += bytes;
+        self
+    }
+
+    /// The error that got further wins; at the same place, both expectations
+    /// are kept.
+    pub fn or(mut self, other: Self) -> Self {
+        if other.remaining < self.remaining {
+            return other;
+        }
+
+        if other.remaining == self.remaining {
+            for expected in other.expected {
+                if !self.expected.contains(&expected) {
+                    self.expected.push(expected);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// The error must already be located within `source`.
+    pub fn render<'a>(&'a self, file_name: &'a str, source: &'a str) -> Diagnostic<'a> {
+        Diagnostic {
+            error: self,
+            file_name,
+            line: source.lines().nth(self.line - 1).unwrap_or(""),
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn expected(&self) -> impl Iterator<Item = &str> {
+        self.expected.iter().map(String::as_str)
+    }
+
+    fn write_expected(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.expected.as_slice() {
+            [expected] => write!(f, "expected {}", expected),
+            expected => write!(f, "expected one of {}", expected.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_expected(f)?;
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub struct Diagnostic<'a> {
+    error: &'a ParseError,
+    file_name: &'a str,
+    line: &'a str,
+}
+
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self.line;
+        let line_number = self.error.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        // Keep tabs so that the caret lines up with the offending character.
+        let caret_indent: String = line
+            .chars()
+            .take(self.error.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        write!(f, "error: ")?;
+        self.error.write_expected(f)?;
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter, self.file_name, self.error.line, self.error.column,
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, line)?;
+        write!(f, "{} | {}^", gutter, caret_indent)
+    }
+}
+
+/// Accepts `\n` and `\r\n`, and drops trailing whitespace and blank lines at
+/// the end.
+pub fn lines(source: &str) -> impl Iterator<Item = &str> {
+    located_lines(source).map(|(_, line)| line)
+}
+
+fn located_lines(source: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut line_start = 0;
+
+    source
+        .trim_end()
+        .split_inclusive('\n')
+        .map(move |line_with_ending| {
+            let start = line_start;
+            line_start += line_with_ending.len();
+
+            (start, line_with_ending.trim_end())
+        })
+}
+
+pub fn parse_lines<'a, T>(
+    source: &'a str,
+    parser: impl Fn(&'a str) -> Result<T, ParseError> + 'a,
+) -> impl Iterator<Item = Result<T, ParseError>> + 'a {
+    located_lines(source).map(move |(start, line)| {
+        parser(line).map_err(|error| error.locate_before(source, start + line.len()))
+    })
+}
+
+/// Each group's lines are split as by [`lines`] and joined with `\n`.
+pub fn groups(source: &str) -> impl Iterator<Item = String> + '_ {
+    located_groups(source).map(|(_, group)| group.text)
+}
+
+fn located_groups(source: &str) -> impl Iterator<Item = (usize, Group)> + '_ {
+    let mut lines = located_lines(source).peekable();
+
+    iter::from_fn(move || {
+        let (start, first_line) = lines.find(|(_, line)| !line.is_empty())?;
+        let mut group = Group::default();
+        group.push(0, first_line);
+
+        while let Some((line_start, line)) = lines.next_if(|(_, line)| !line.is_empty()) {
+            group.push(line_start - start, line);
+        }
+
+        Some((start, group))
+    })
+}
+
+pub fn parse_groups<'a, T>(
+    source: &'a str,
+    parser: impl Fn(&str) -> Result<T, ParseError> + 'a,
+) -> impl Iterator<Item = Result<T, ParseError>> + 'a {
+    located_groups(source).map(move |(start, group)| {
+        parser(&group.text).map_err(|error| {
+            let offset = start + group.original_offset(group.text.len() - error.remaining);
+            let end = offset + error.remaining;
+
+            error.locate_before(source, end)
+        })
+    })
+}
+
+/// `line_starts` are offsets from the start of the group in the original
+/// input.
+#[derive(Default)]
+struct Group {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl Group {
+    fn push(&mut self, start: usize, line: &str) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+
+        self.text.push_str(line);
+        self.line_starts.push(start);
+    }
+
+    fn original_offset(&self, offset: usize) -> usize {
+        let before = &self.text.as_bytes()[..offset];
+        let line = before.iter().filter(|&&b| b == b'\n').count();
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |idx| idx + 1);
+
+        self.line_starts[line] + offset - line_start
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+        self.line_starts.clear();
+    }
+}
+
+pub fn parse_all_lines<'a, T>(
+    source: &'a str,
+    parser: impl Fn(&'a str) -> Result<T, ParseError> + 'a,
+) -> Result<Vec<T>, Vec<ParseError>> {
+    collect_all(parse_lines(source, parser))
+}
+
+pub fn collect_all<T, E>(
+    results: impl IntoIterator<Item = Result<T, E>>,
+) -> Result<Vec<T>, Vec<E>> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(error) => errors.push(error),
+        }
+    }
 
-        s = if let Ok(s) = separator_parser(new_s) {
-            s
-        } else {
-            break;
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse { error: ParseError, line: String },
+}
+
+impl ReadError {
+    /// `error` must already be located within `record`.
+    fn new(error: ParseError, record: &str, lines_before: usize, bytes_before: usize) -> Self {
+        let line = record.lines().nth(error.line - 1).unwrap_or("").to_string();
+
+        Self::Parse {
+            error: error.shift(lines_before, bytes_before),
+            line,
+        }
+    }
+
+    pub fn render(&self, file_name: &str) -> String {
+        match self {
+            Self::Io(error) => format!("error: could not read {}: {}", file_name, error),
+            Self::Parse { error, line } => Diagnostic {
+                error,
+                file_name,
+                line,
+            }
+            .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Parse { error, .. } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+pub fn read_lines<T>(
+    reader: impl BufRead,
+    parser: impl Fn(&str) -> Result<T, ParseError>,
+) -> impl Iterator<Item = Result<T, ReadError>> {
+    let mut records = Records::new(reader);
+
+    // Blank lines are held back until we know whether they are at the end of
+    // the input, where they are ignored.
+    let mut blank_lines = Vec::new();
+    let mut pending = VecDeque::new();
+
+    iter::from_fn(move || loop {
+        if let Some(result) = pending.pop_front() {
+            return Some(result);
+        }
+
+        let (lines_before, bytes_before) = (records.lines_read, records.bytes_read);
+
+        let line = match records.next_line()? {
+            Ok(line) => line.trim_end(),
+            Err(error) => return Some(Err(error.into())),
         };
+
+        if line.is_empty() {
+            blank_lines.push((lines_before, bytes_before));
+            continue;
+        }
+
+        for (lines_before, bytes_before) in blank_lines.drain(..) {
+            pending.push_back(
+                parser("").map_err(|error| {
+                    ReadError::new(error.locate(""), "", lines_before, bytes_before)
+                }),
+            );
+        }
+
+        pending.push_back(
+            parser(line).map_err(|error| {
+                ReadError::new(error.locate(line), line, lines_before, bytes_before)
+            }),
+        );
+    })
+}
+
+pub fn read_groups<T>(
+    reader: impl BufRead,
+    parser: impl Fn(&str) -> Result<T, ParseError>,
+) -> impl Iterator<Item = Result<T, ReadError>> {
+    let mut records = Records::new(reader);
+    let mut group = Group::default();
+    let mut group_start = (0, 0);
+
+    iter::from_fn(move || {
+        loop {
+            let (lines_before, bytes_before) = (records.lines_read, records.bytes_read);
+
+            match records.next_line() {
+                Some(Ok(line)) if line.trim_end().is_empty() => {
+                    if !group.text.is_empty() {
+                        break;
+                    }
+                }
+                Some(Ok(line)) => {
+                    if group.text.is_empty() {
+                        group_start = (lines_before, bytes_before);
+                    }
+
+                    group.push(bytes_before - group_start.1, line.trim_end());
+                }
+                Some(Err(error)) => return Some(Err(error.into())),
+                None if group.text.is_empty() => return None,
+                None => break,
+            }
+        }
+
+        let (lines_before, bytes_before) = group_start;
+        let result = parser(&group.text).map_err(|error| {
+            let mut error = error.locate(&group.text);
+            error.offset = group.original_offset(error.offset);
+
+            ReadError::new(error, &group.text, lines_before, bytes_before)
+        });
+
+        group.clear();
+        Some(result)
+    })
+}
+
+struct Records<R> {
+    reader: R,
+    line: String,
+    lines_read: usize,
+    bytes_read: usize,
+    failed: bool,
+}
+
+impl<R: BufRead> Records<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            lines_read: 0,
+            bytes_read: 0,
+            failed: false,
+        }
+    }
+
+    /// Includes the line ending.
+    fn next_line(&mut self) -> Option<io::Result<&str>> {
+        if self.failed {
+            return None;
+        }
+
+        self.line.clear();
+
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => None,
+            Ok(len) => {
+                self.lines_read += 1;
+                self.bytes_read += len;
+                Some(Ok(&self.line))
+            }
+            Err(error) => {
+                self.failed = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+pub fn all_consuming<'a, T>(
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T), ParseError>,
+    s: &'a str,
+) -> Result<T, ParseError> {
+    let (remainder, value) = parser(s).map_err(|error| error.locate(s))?;
+
+    if !remainder.is_empty() {
+        return Err(ParseError::new(remainder, "end of input").locate(s));
+    }
+
+    Ok(value)
+}
+
+/// A tuple of parsers for [`alt`] to try in order.
+pub trait Alt<'a, T> {
+    fn parse(&self, s: &'a str) -> Result<(&'a str, T), ParseError>;
+}
+
+macro_rules! impl_alt {
+    ($first:ident $(, $rest:ident)*) => {
+        impl<'a, T, $first, $($rest),*> Alt<'a, T> for ($first, $($rest,)*)
+        where
+            $first: Fn(&'a str) -> Result<(&'a str, T), ParseError>,
+            $($rest: Fn(&'a str) -> Result<(&'a str, T), ParseError>,)*
+        {
+            #[allow(non_snake_case)]
+            fn parse(&self, s: &'a str) -> Result<(&'a str, T), ParseError> {
+                let ($first, $($rest,)*) = self;
+
+                let error = match $first(s) {
+                    Ok(parsed) => return Ok(parsed),
+                    Err(error) => error,
+                };
+
+                $(
+                    let error = match $rest(s) {
+                        Ok(parsed) => return Ok(parsed),
+                        Err(other) => error.or(other),
+                    };
+                )*
+
+                Err(error)
+            }
+        }
+
+        impl_alt!($($rest),*);
+    };
+    () => {};
+}
+
+impl_alt!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+pub fn alt<'a, T>(parsers: impl Alt<'a, T>, s: &'a str) -> Result<(&'a str, T), ParseError> {
+    parsers.parse(s)
+}
+
+pub fn opt<'a, T>(
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T), ParseError>,
+    s: &'a str,
+) -> Result<(&'a str, Option<T>), ParseError> {
+    match parser(s) {
+        Ok((s, value)) => Ok((s, Some(value))),
+        Err(_) => Ok((s, None)),
+    }
+}
+
+pub fn map<'a, T, U>(
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T), ParseError>,
+    f: impl FnOnce(T) -> U,
+    s: &'a str,
+) -> Result<(&'a str, U), ParseError> {
+    let (s, value) = parser(s)?;
+    Ok((s, f(value)))
+}
+
+pub fn preceded<'a, T>(
+    prefix_parser: impl FnOnce(&'a str) -> Result<&'a str, ParseError>,
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T), ParseError>,
+    s: &'a str,
+) -> Result<(&'a str, T), ParseError> {
+    let s = prefix_parser(s)?;
+    parser(s)
+}
+
+pub fn delimited<'a, T>(
+    prefix_parser: impl FnOnce(&'a str) -> Result<&'a str, ParseError>,
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T), ParseError>,
+    suffix_parser: impl FnOnce(&'a str) -> Result<&'a str, ParseError>,
+    s: &'a str,
+) -> Result<(&'a str, T), ParseError> {
+    let (s, value) = preceded(prefix_parser, parser, s)?;
+    let s = suffix_parser(s)?;
+
+    Ok((s, value))
+}
+
+/// A separator is only consumed if another item follows it.
+pub fn separated_list<'a, T>(
+    parser: impl Fn(&'a str) -> Result<(&'a str, T), ParseError>,
+    separator_parser: impl Fn(&'a str) -> Result<&'a str, ParseError>,
+    s: &'a str,
+) -> Result<(&'a str, Vec<T>), ParseError> {
+    separated_list1(parser, separator_parser, s).or_else(|_| Ok((s, Vec::new())))
+}
+
+pub fn separated_list1<'a, T>(
+    parser: impl Fn(&'a str) -> Result<(&'a str, T), ParseError>,
+    separator_parser: impl Fn(&'a str) -> Result<&'a str, ParseError>,
+    s: &'a str,
+) -> Result<(&'a str, Vec<T>), ParseError> {
+    let (mut s, first_item) = parser(s)?;
+    let mut items = vec![first_item];
+
+    while let Ok((new_s, item)) = separator_parser(s).and_then(&parser) {
+        s = new_s;
+        items.push(item);
     }
 
     Ok((s, items))
 }
 
-pub(crate) fn sequence1<T>(
-    parser: impl Fn(&str) -> Result<(&str, T), String>,
-    separator_parser: impl Fn(&str) -> Result<&str, String>,
-    s: &str,
-) -> Result<(&str, Vec<T>), String> {
-    let (s, sequence) = sequence(parser, separator_parser, s)?;
+/// Each `{}` in `pattern` is a field, parsed in order into `T`, and `{{` and
+/// `}}` match literal braces. A `&str` field stops at whitespace.
+pub fn template<'a, T: Fields<'a>>(pattern: &str, s: &'a str) -> Result<(&'a str, T), ParseError> {
+    let mut pattern =
+        Pattern::new(pattern, T::LEN).map_err(|expected| ParseError::new(s, expected))?;
+    let (s, fields) = T::parse_fields(&mut pattern, s)?;
+    let s = pattern.end(s)?;
+
+    Ok((s, fields))
+}
+
+pub trait Field<'a>: Sized {
+    fn parse_field(s: &'a str) -> Result<(&'a str, Self), ParseError>;
+}
+
+pub trait Fields<'a>: Sized {
+    const LEN: usize;
+
+    fn parse_fields(pattern: &mut Pattern, s: &'a str) -> Result<(&'a str, Self), ParseError>;
+}
+
+pub struct Pattern {
+    literals: vec::IntoIter<String>,
+}
+
+impl Pattern {
+    fn new(pattern: &str, num_fields: usize) -> Result<Self, String> {
+        let mut literals = vec![String::new()];
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literals.last_mut().unwrap().push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literals.last_mut().unwrap().push('}');
+                }
+                '{' => {
+                    let (name, rest) = chars
+                        .as_str()
+                        .split_once('}')
+                        .ok_or("a template with every field closed")?;
+
+                    if name.contains('{') {
+                        return Err("a template with every field closed".to_string());
+                    }
+
+                    chars = rest.chars();
+                    literals.push(String::new());
+                }
+                '}' => return Err("a template with `}}` for each literal `}`".to_string()),
+                c => literals.last_mut().unwrap().push(c),
+            }
+        }
+
+        if literals.len() != num_fields + 1 {
+            let fields = if num_fields == 1 { "field" } else { "fields" };
+
+            return Err(format!(
+                "a template with {} {}, not {}",
+                num_fields,
+                fields,
+                literals.len() - 1,
+            ));
+        }
+
+        Ok(Self {
+            literals: literals.into_iter(),
+        })
+    }
+
+    pub fn next_field<'a>(&mut self, s: &'a str) -> Result<&'a str, ParseError> {
+        tag(&self.literals.next().unwrap_or_default(), s)
+    }
+
+    fn end(mut self, s: &str) -> Result<&str, ParseError> {
+        self.next_field(s)
+    }
+}
+
+macro_rules! impl_fields {
+    ($($field:ident),+) => {
+        impl<'a, $($field: Field<'a>),+> Fields<'a> for ($($field,)+) {
+            const LEN: usize = [$(stringify!($field)),+].len();
+
+            #[allow(non_snake_case)]
+            fn parse_fields(
+                pattern: &mut Pattern,
+                s: &'a str,
+            ) -> Result<(&'a str, Self), ParseError> {
+                $(
+                    let s = pattern.next_field(s)?;
+                    let (s, $field) = $field::parse_field(s)?;
+                )+
+
+                Ok((s, ($($field,)+)))
+            }
+        }
+    };
+}
+
+impl_fields!(A);
+impl_fields!(A, B);
+impl_fields!(A, B, C);
+impl_fields!(A, B, C, D);
+impl_fields!(A, B, C, D, E);
+impl_fields!(A, B, C, D, E, F);
+
+macro_rules! impl_field {
+    ($parser:ident: $($number:ty),+) => {
+        $(
+            impl Field<'_> for $number {
+                fn parse_field(s: &str) -> Result<(&str, Self), ParseError> {
+                    $parser(s)
+                }
+            }
+        )+
+    };
+}
+
+impl_field!(unsigned: u8, u16, u32, u64, usize);
+impl_field!(signed: i8, i16, i32, i64, isize);
+
+impl Field<'_> for char {
+    fn parse_field(s: &str) -> Result<(&str, Self), ParseError> {
+        extract_char(s)
+    }
+}
+
+impl<'a> Field<'a> for &'a str {
+    fn parse_field(s: &'a str) -> Result<(&'a str, Self), ParseError> {
+        take_while1(|c| !c.is_whitespace(), "a word", s)
+    }
+}
+
+/// Fails if the number does not fit in `T`.
+pub fn unsigned<T: FromStr>(s: &str) -> Result<(&str, T), ParseError> {
+    let (remainder, digits) = extract_digits(s)?;
+    Ok((remainder, parse_number(digits, s)?))
+}
+
+pub fn signed<T: FromStr>(s: &str) -> Result<(&str, T), ParseError> {
+    let sign_len = if s.starts_with('+') || s.starts_with('-') {
+        1
+    } else {
+        0
+    };
+
+    let (remainder, _) = extract_digits(&s[sign_len..])?;
+    let number = &s[..s.len() - remainder.len()];
+
+    Ok((remainder, parse_number(number, s)?))
+}
+
+pub fn in_range<'a, T: PartialOrd>(
+    parser: impl FnOnce(&'a str) -> Result<(&'a str, T), ParseError>,
+    range: impl RangeBounds<T> + fmt::Debug,
+    s: &'a str,
+) -> Result<(&'a str, T), ParseError> {
+    let (remainder, number) = parser(s)?;
 
-    if sequence.is_empty() {
-        Err("expected a sequence with more than one item".to_string())
+    if range.contains(&number) {
+        Ok((remainder, number))
     } else {
-        Ok((s, sequence))
+        Err(ParseError::new(s, format!("a number in {:?}", range)))
     }
 }
 
-pub(crate) fn tag<'a, 'b>(starting_text: &'a str, s: &'b str) -> Result<&'b str, String> {
+fn parse_number<T: FromStr>(number: &str, s: &str) -> Result<T, ParseError> {
+    number.parse().map_err(|_| {
+        ParseError::new(
+            s,
+            format!("a number that fits in {}", std::any::type_name::<T>()),
+        )
+    })
+}
+
+pub fn tag<'b>(starting_text: &str, s: &'b str) -> Result<&'b str, ParseError> {
     s.strip_prefix(starting_text)
-        .ok_or_else(|| format!("expected {}", starting_text))
+        .ok_or_else(|| ParseError::new(s, format!("{:?}", starting_text)))
 }
 
-pub(crate) fn extract_digits(s: &str) -> Result<(&str, &str), String> {
-    take_while1(|c| c.is_ascii_digit(), s, "expected digits".to_string())
+pub fn opt_tag<'b>(starting_text: &str, s: &'b str) -> (&'b str, bool) {
+    match s.strip_prefix(starting_text) {
+        Some(s) => (s, true),
+        None => (s, false),
+    }
 }
 
-pub(crate) fn extract_lowercase(s: &str) -> Result<(&str, &str), String> {
-    take_while1(
-        |c| c.is_ascii_lowercase(),
-        s,
-        "expected lowercase letters".to_string(),
-    )
+pub fn extract_digits(s: &str) -> Result<(&str, &str), ParseError> {
+    take_while1(|c| c.is_ascii_digit(), "digits", s)
 }
 
-pub(crate) fn extract_char(s: &str) -> Result<(&str, char), String> {
-    let gen_error_msg = || "expected a character".to_string();
+pub fn extract_lowercase(s: &str) -> Result<(&str, &str), ParseError> {
+    take_while1(|c| c.is_ascii_lowercase(), "lowercase letters", s)
+}
 
-    let c = s.chars().next().ok_or_else(gen_error_msg)?;
-    let (idx, _) = s.char_indices().nth(1).ok_or_else(gen_error_msg)?;
+pub fn extract_char(s: &str) -> Result<(&str, char), ParseError> {
+    let c = s
+        .chars()
+        .next()
+        .ok_or_else(|| ParseError::new(s, "a character"))?;
 
-    Ok((&s[idx..], c))
+    Ok((&s[c.len_utf8()..], c))
 }
 
-fn take_while1(
+pub fn take_while1<'a>(
     accept: impl Fn(char) -> bool,
-    s: &str,
-    error_msg: String,
-) -> Result<(&str, &str), String> {
+    expected: &str,
+    s: &'a str,
+) -> Result<(&'a str, &'a str), ParseError> {
     let (remainder, extracted) = take_while(accept, s);
 
     if extracted.is_empty() {
-        Err(error_msg)
+        Err(ParseError::new(s, expected))
     } else {
         Ok((remainder, extracted))
     }
@@ -77,9 +804,424 @@ fn take_while(accept: impl Fn(char) -> bool, s: &str) -> (&str, &str) {
     let extracted_end = s
         .char_indices()
         .find_map(|(idx, c)| if accept(c) { None } else { Some(idx) })
-        .unwrap_or_else(|| s.len());
+        .unwrap_or(s.len());
 
     let extracted = &s[..extracted_end];
     let remainder = &s[extracted_end..];
     (remainder, extracted)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_error_on_first_line() {
+        let source = "123 -> x";
+        let error = tag(" <- ", &source[3..]).unwrap_err().locate(source);
+
+        assert_eq!(error.offset(), 3);
+        assert_eq!(error.line(), 1);
+        assert_eq!(error.column(), 4);
+        assert_eq!(error.expected().collect::<Vec<_>>(), [r#"" <- ""#]);
+    }
+
+    #[test]
+    fn locate_error_on_later_line() {
+        let source = "abc\ndef\ngh1";
+        let error = extract_digits(&source[8..]).unwrap_err().locate(source);
+
+        assert_eq!(error.offset(), 8);
+        assert_eq!(error.line(), 3);
+        assert_eq!(error.column(), 1);
+    }
+
+    #[test]
+    fn keep_error_that_got_further() {
+        let source = "abc1";
+        let near = extract_digits(source).unwrap_err();
+        let far = tag("x", &source[3..]).unwrap_err();
+
+        assert_eq!(near.clone().or(far.clone()), far);
+        assert_eq!(far.clone().or(near), far);
+    }
+
+    #[test]
+    fn combine_errors_at_same_position() {
+        let source = "xyz";
+        let error = tag("a", source)
+            .unwrap_err()
+            .or(tag("b", source).unwrap_err())
+            .or(tag("a", source).unwrap_err());
+
+        assert_eq!(error.expected().collect::<Vec<_>>(), [r#""a""#, r#""b""#]);
+    }
+
+    #[test]
+    fn locate_errors_from_lines_within_source() {
+        let source = "12\n34\nab\n";
+        let errors: Vec<_> = parse_lines(source, |line| extract_digits(line).map(|_| ()))
+            .filter_map(Result::err)
+            .collect();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].offset(), 6);
+        assert_eq!(errors[0].line(), 3);
+        assert_eq!(errors[0].column(), 1);
+    }
+
+    #[test]
+    fn split_lines_with_any_ending() {
+        let source = "ab\r\ncd  \nef\t\r\n\n\r\n";
+        assert_eq!(lines(source).collect::<Vec<_>>(), ["ab", "cd", "ef"]);
+    }
+
+    #[test]
+    fn split_groups_with_any_ending() {
+        let source = "ab\r\ncd\r\n\r\nef\n\n \ngh\n\n";
+        assert_eq!(groups(source).collect::<Vec<_>>(), ["ab\ncd", "ef", "gh"]);
+    }
+
+    #[test]
+    fn give_group_parsers_normalized_lines() {
+        let source = "ab \r\ncd\r\n\r\nef\t\r\n";
+        let expected = ["ab\ncd", "ef"];
+
+        let parsed: Vec<_> = parse_groups(source, |group| Ok(group.to_string()))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(parsed, expected);
+
+        let read: Vec<_> = read_groups(source.as_bytes(), |group| Ok(group.to_string()))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn locate_errors_from_crlf_groups_within_source() {
+        let source = "12\r\n\r\n34 \r\n5a\r\n";
+        let parser = |group: &str| {
+            all_consuming(
+                |s| separated_list1(extract_digits, |s| tag("\n", s), s),
+                group,
+            )
+            .map(|digits| digits.len())
+        };
+
+        let error = parse_groups(source, parser).find_map(Result::err).unwrap();
+        assert_eq!((error.offset(), error.line(), error.column()), (12, 4, 2));
+
+        match read_groups(source.as_bytes(), parser).find_map(Result::err) {
+            Some(ReadError::Parse { error, line }) => {
+                assert_eq!((error.offset(), error.line(), error.column()), (12, 4, 2));
+                assert_eq!(line, "5a");
+            }
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn locate_errors_from_crlf_lines_within_source() {
+        let source = "12\r\nab\r\n";
+        let error = parse_lines(source, |line| extract_digits(line).map(|_| ()))
+            .find_map(Result::err)
+            .unwrap();
+
+        assert_eq!(error.offset(), 4);
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 1);
+    }
+
+    #[test]
+    fn read_lines_one_at_a_time() {
+        let source = "12\r\n34\n\n";
+        let lines: Vec<_> = read_lines(source.as_bytes(), |line| {
+            all_consuming(extract_digits, line).map(str::to_string)
+        })
+        .map(Result::unwrap)
+        .collect();
+
+        assert_eq!(lines, ["12", "34"]);
+    }
+
+    #[test]
+    fn locate_errors_from_read_lines_within_input() {
+        let source = "12\r\n\r\n3a\n";
+        let errors: Vec<_> = read_lines(source.as_bytes(), |line| {
+            all_consuming(extract_digits, line).map(|_| ())
+        })
+        .filter_map(Result::err)
+        .collect();
+
+        let positions: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                ReadError::Parse { error, line } => {
+                    (error.offset(), error.line(), error.column(), line.as_str())
+                }
+                ReadError::Io(error) => panic!("unexpected I/O error: {}", error),
+            })
+            .collect();
+
+        assert_eq!(positions, [(4, 2, 1, ""), (7, 3, 2, "3a")]);
+    }
+
+    #[test]
+    fn read_groups_one_at_a_time() {
+        let source = "1\n2\r\n\r\n\r\n3\n4a\n";
+        let results: Vec<_> = read_groups(source.as_bytes(), |group| {
+            all_consuming(
+                |s| separated_list1(extract_digits, |s| tag("\n", s), s),
+                group,
+            )
+            .map(|digits| digits.len())
+        })
+        .collect();
+
+        assert_eq!(results[0].as_ref().unwrap(), &2);
+
+        match &results[1] {
+            Err(ReadError::Parse { error, line }) => {
+                assert_eq!((error.line(), error.column()), (6, 2));
+                assert_eq!(line, "4a");
+            }
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn report_every_bad_line() {
+        let source = "12\nab\n34\n5c\n";
+        let errors =
+            parse_all_lines(source, |line| all_consuming(extract_digits, line)).unwrap_err();
+
+        let lines: Vec<_> = errors.iter().map(ParseError::line).collect();
+        assert_eq!(lines, [2, 4]);
+    }
+
+    #[test]
+    fn return_every_value_when_no_line_is_bad() {
+        let source = "12\n34\n";
+
+        assert_eq!(
+            parse_all_lines(source, |line| all_consuming(extract_digits, line)),
+            Ok(vec!["12", "34"]),
+        );
+    }
+
+    #[test]
+    fn locate_errors_from_groups_within_source() {
+        let source = "12\n34\n\n56\nab\n";
+        let results: Vec<_> = parse_groups(source, |group| {
+            all_consuming(
+                |s| separated_list1(extract_digits, |s| tag("\n", s), s),
+                group,
+            )
+            .map(|digits| digits.len())
+        })
+        .collect();
+
+        assert_eq!(results[0], Ok(2));
+
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!(error.line(), 4);
+        assert_eq!(error.column(), 3);
+    }
+
+    #[test]
+    fn parse_template() {
+        assert_eq!(
+            template("{min}-{max} {letter}: {password}", "1-3 a: abcde"),
+            Ok(("", (1u8, 3u8, 'a', "abcde"))),
+        );
+    }
+
+    #[test]
+    fn report_mismatched_template_text() {
+        let source = "1-3 a; abcde";
+        let error = template::<(u8, u8, char, &str)>("{}-{} {}: {}", source)
+            .unwrap_err()
+            .locate(source);
+
+        assert_eq!(error.column(), 6);
+        assert_eq!(error.expected().collect::<Vec<_>>(), [r#"": ""#]);
+    }
+
+    #[test]
+    fn report_bad_template_field() {
+        let source = "1-300 a: abcde";
+        let error = template::<(u8, u8, char, &str)>("{}-{} {}: {}", source)
+            .unwrap_err()
+            .locate(source);
+
+        assert_eq!(error.column(), 3);
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            ["a number that fits in u8"],
+        );
+    }
+
+    #[test]
+    fn reject_template_with_wrong_number_of_fields() {
+        assert_eq!(
+            template::<(u8,)>("{}-{}", "1-3"),
+            Err(ParseError::new("1-3", "a template with 1 field, not 2")),
+        );
+        assert!(template::<(u8, u8, u8)>("{}-{}", "1-3").is_err());
+    }
+
+    #[test]
+    fn reject_malformed_template() {
+        assert!(template::<(u8,)>("{a", "1").is_err());
+        assert!(template::<(u8,)>("{a{}", "1").is_err());
+        assert!(template::<(u8,)>("{}}", "1}").is_err());
+    }
+
+    #[test]
+    fn match_escaped_braces_in_template() {
+        assert_eq!(template("{{{}}}: {}", "{7}: x"), Ok(("", (7u8, 'x'))),);
+    }
+
+    #[test]
+    fn render_diagnostic() {
+        let source = "123 -> x\nx ANDD y -> z\n";
+        let error = tag(" ", &source[14..]).unwrap_err().locate(source);
+
+        assert_eq!(
+            error.render("circuit_input", source).to_string(),
+            r#"error: expected " "
+ --> circuit_input:2:6
+  |
+2 | x ANDD y -> z
+  |      ^"#,
+        );
+    }
+
+    #[test]
+    fn all_consuming_rejects_leftover_input() {
+        let error = all_consuming(extract_digits, "12ab").unwrap_err();
+
+        assert_eq!(error.column(), 3);
+        assert_eq!(error.expected().collect::<Vec<_>>(), ["end of input"]);
+    }
+
+    #[test]
+    fn alt_takes_first_success() {
+        let parser = |s| {
+            alt(
+                (
+                    |s| tag("a", s).map(|s| (s, 1)),
+                    |s| tag("ab", s).map(|s| (s, 2)),
+                ),
+                s,
+            )
+        };
+
+        assert_eq!(parser("abc"), Ok(("bc", 1)));
+        assert_eq!(
+            parser("xyz").unwrap_err().expected().collect::<Vec<_>>(),
+            [r#""a""#, r#""ab""#],
+        );
+    }
+
+    #[test]
+    fn opt_does_not_consume_on_failure() {
+        assert_eq!(opt(extract_digits, "12a"), Ok(("a", Some("12"))));
+        assert_eq!(opt(extract_digits, "a"), Ok(("a", None)));
+    }
+
+    #[test]
+    fn opt_tag_reports_whether_it_matched() {
+        assert_eq!(opt_tag("s", "s."), (".", true));
+        assert_eq!(opt_tag("s", "."), (".", false));
+    }
+
+    #[test]
+    fn map_output() {
+        assert_eq!(map(extract_digits, str::len, "123a"), Ok(("a", 3)));
+    }
+
+    #[test]
+    fn preceded_and_delimited() {
+        assert_eq!(
+            preceded(|s| tag("#", s), extract_digits, "#12"),
+            Ok(("", "12")),
+        );
+
+        assert_eq!(
+            delimited(|s| tag("(", s), extract_lowercase, |s| tag(")", s), "(ab)c"),
+            Ok(("c", "ab")),
+        );
+    }
+
+    #[test]
+    fn separated_list_leaves_trailing_separator() {
+        assert_eq!(
+            separated_list(extract_digits, |s| tag(",", s), "1,2,3,a"),
+            Ok((",a", vec!["1", "2", "3"])),
+        );
+
+        assert_eq!(
+            separated_list(extract_digits, |s| tag(",", s), "a"),
+            Ok(("a", Vec::new())),
+        );
+    }
+
+    #[test]
+    fn separated_list1_requires_an_item() {
+        let error = separated_list1(extract_digits, |s| tag(",", s), "a").unwrap_err();
+        assert_eq!(error.expected().collect::<Vec<_>>(), ["digits"]);
+    }
+
+    #[test]
+    fn parse_unsigned_number() {
+        assert_eq!(unsigned::<u16>("65535 -> a"), Ok((" -> a", 65535)));
+        assert!(unsigned::<u16>("-1").is_err());
+    }
+
+    #[test]
+    fn reject_number_that_overflows() {
+        let error = unsigned::<u16>("70000").unwrap_err();
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            ["a number that fits in u16"],
+        );
+    }
+
+    #[test]
+    fn reject_number_out_of_range() {
+        assert_eq!(in_range(unsigned, 0..1000, "999"), Ok(("", 999)));
+
+        let error = in_range(unsigned, 0..1000, "1000").unwrap_err();
+        assert_eq!(
+            error.expected().collect::<Vec<_>>(),
+            ["a number in 0..1000"]
+        );
+    }
+
+    #[test]
+    fn parse_signed_number() {
+        assert_eq!(signed::<i32>("+12"), Ok(("", 12)));
+        assert_eq!(signed::<i32>("-12 "), Ok((" ", -12)));
+        assert_eq!(signed::<i32>("12"), Ok(("", 12)));
+        assert!(signed::<i32>("+").is_err());
+    }
+
+    #[test]
+    fn extract_last_character() {
+        assert_eq!(extract_char("a"), Ok(("", 'a')));
+        assert_eq!(extract_char("éx"), Ok(("x", 'é')));
+    }
+
+    #[test]
+    fn display_error() {
+        let source = "ab\n12x";
+        let error = extract_lowercase(&source[3..]).unwrap_err().locate(source);
+
+        assert_eq!(
+            error.to_string(),
+            "expected lowercase letters at line 2, column 1",
+        );
+    }
+}
@@ -1,18 +1,23 @@
+use crate::parsing::{all_consuming, read_groups, tag, take_while1, ParseError, ReadError};
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::BufRead;
+use std::ops::RangeInclusive;
 
-pub struct Passport<'a> {
-    birth_year: Option<&'a str>,
-    issue_year: Option<&'a str>,
-    expiration_year: Option<&'a str>,
-    height: Option<&'a str>,
-    hair_color: Option<&'a str>,
-    eye_color: Option<&'a str>,
-    passport_id: Option<&'a str>,
+#[derive(Debug)]
+pub struct Passport {
+    birth_year: Option<String>,
+    issue_year: Option<String>,
+    expiration_year: Option<String>,
+    height: Option<String>,
+    hair_color: Option<String>,
+    eye_color: Option<String>,
+    passport_id: Option<String>,
     #[allow(dead_code)]
-    country_id: Option<&'a str>,
+    country_id: Option<String>,
 }
 
-impl Passport<'_> {
+impl Passport {
     pub fn is_complete(&self) -> bool {
         self.birth_year.is_some()
             && self.issue_year.is_some()
@@ -28,55 +33,119 @@ impl Passport<'_> {
             return false;
         }
 
-        let birth_year = self.birth_year.unwrap();
-        let issue_year = self.issue_year.unwrap();
-        let expiration_year = self.expiration_year.unwrap();
-        let height = self.height.unwrap();
-        let hair_color = self.hair_color.unwrap();
-        let eye_color = self.eye_color.unwrap();
-        let passport_id = self.passport_id.unwrap();
-
-        (1920..=2002).contains(&birth_year.parse().unwrap())
-            && (2010..=2020).contains(&issue_year.parse().unwrap())
-            && (2020..=2030).contains(&expiration_year.parse().unwrap())
+        let birth_year = self.birth_year.as_deref().unwrap();
+        let issue_year = self.issue_year.as_deref().unwrap();
+        let expiration_year = self.expiration_year.as_deref().unwrap();
+        let height = self.height.as_deref().unwrap();
+        let hair_color = self.hair_color.as_deref().unwrap();
+        let eye_color = self.eye_color.as_deref().unwrap();
+        let passport_id = self.passport_id.as_deref().unwrap();
+
+        let is_number_in = |s: &str, range: RangeInclusive<u32>| {
+            s.parse().is_ok_and(|number| range.contains(&number))
+        };
+
+        is_number_in(birth_year, 1920..=2002)
+            && is_number_in(issue_year, 2010..=2020)
+            && is_number_in(expiration_year, 2020..=2030)
             && height.strip_suffix("cm").map_or_else(
                 || {
                     height
                         .strip_suffix("in")
-                        .map_or(false, |in_| (59..=76).contains(&in_.parse().unwrap()))
+                        .is_some_and(|in_| is_number_in(in_, 59..=76))
                 },
-                |cm| (150..=193).contains(&cm.parse().unwrap()),
+                |cm| is_number_in(cm, 150..=193),
             )
-            && hair_color.strip_prefix('#').map_or(false, |hex| {
-                hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())
-            })
+            && hair_color
+                .strip_prefix('#')
+                .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
             && ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(&eye_color)
             && passport_id.len() == 9
             && passport_id.chars().all(|c| c.is_ascii_digit())
     }
 }
 
-impl<'a> From<&'a str> for Passport<'a> {
-    fn from(s: &'a str) -> Self {
-        let fields: HashMap<_, _> = s
-            .split_whitespace()
-            .map(|field| {
-                let colon_idx = field.find(':').unwrap();
-                let (field_name, field_value) = (&field[..colon_idx], &field[colon_idx + 1..]);
-
-                (field_name, field_value)
-            })
-            .collect();
-
-        Passport {
-            birth_year: fields.get("byr").copied(),
-            issue_year: fields.get("iyr").copied(),
-            expiration_year: fields.get("eyr").copied(),
-            height: fields.get("hgt").copied(),
-            hair_color: fields.get("hcl").copied(),
-            eye_color: fields.get("ecl").copied(),
-            passport_id: fields.get("pid").copied(),
-            country_id: fields.get("cid").copied(),
+impl Passport {
+    pub fn read_all(reader: impl BufRead) -> impl Iterator<Item = Result<Self, ReadError>> {
+        read_groups(reader, |group| Self::try_from(group))
+    }
+
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        let mut fields = HashMap::new();
+        let mut s = s.trim_start();
+
+        while !s.is_empty() {
+            let (new_s, (field_name, field_value)) = Self::new_field(s)?;
+            s = new_s.trim_start();
+
+            fields.insert(field_name, field_value);
         }
+
+        let passport = Passport {
+            birth_year: fields.remove("byr").map(str::to_string),
+            issue_year: fields.remove("iyr").map(str::to_string),
+            expiration_year: fields.remove("eyr").map(str::to_string),
+            height: fields.remove("hgt").map(str::to_string),
+            hair_color: fields.remove("hcl").map(str::to_string),
+            eye_color: fields.remove("ecl").map(str::to_string),
+            passport_id: fields.remove("pid").map(str::to_string),
+            country_id: fields.remove("cid").map(str::to_string),
+        };
+
+        Ok((s, passport))
+    }
+
+    fn new_field(s: &str) -> Result<(&str, (&str, &str)), ParseError> {
+        let (s, field_name) = take_while1(|c| c != ':' && !c.is_whitespace(), "a field name", s)?;
+
+        let s = tag(":", s)?;
+        let (s, field_value) = take_while1(|c| !c.is_whitespace(), "a field value", s)?;
+
+        Ok((s, (field_name, field_value)))
+    }
+}
+
+impl TryFrom<&str> for Passport {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        all_consuming(Self::new, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_passport_spread_over_lines() {
+        let passport = Passport::try_from(
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+",
+        )
+        .unwrap();
+
+        assert!(passport.is_complete());
+        assert!(passport.is_valid());
+    }
+
+    #[test]
+    fn reject_field_without_value() {
+        let error = Passport::try_from("ecl:gry pid").unwrap_err();
+
+        assert_eq!(error.column(), 12);
+        assert_eq!(error.expected().collect::<Vec<_>>(), [r#"":""#]);
+    }
+
+    #[test]
+    fn invalid_when_year_is_not_a_number() {
+        let passport = Passport::try_from(
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:19x7 iyr:2017 hgt:183cm",
+        )
+        .unwrap();
+
+        assert!(passport.is_complete());
+        assert!(!passport.is_valid());
     }
 }
@@ -1,5 +1,6 @@
-use crate::parsing::{extract_char, extract_digits, tag};
+use crate::parsing::{all_consuming, template, ParseError};
 use std::convert::TryFrom;
+use std::fmt;
 
 #[derive(Debug, PartialEq)]
 pub struct Password<'a> {
@@ -7,23 +8,38 @@ pub struct Password<'a> {
     password: &'a str,
 }
 
+impl<'a> Password<'a> {
+    fn new(s: &'a str) -> Result<(&'a str, Self), ParseError> {
+        let (s, (num1, num2, letter, password)) = template("{min}-{max} {letter}: {password}", s)?;
+
+        Ok((
+            s,
+            Self {
+                requirements: Requirements { num1, num2, letter },
+                password,
+            },
+        ))
+    }
+}
+
 impl<'a> TryFrom<&'a str> for Password<'a> {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        let (s, requirements) = Requirements::new(s)?;
-        let s = tag(": ", s)?;
-
-        Ok(Self {
-            requirements,
-            password: s,
-        })
+        all_consuming(Self::new, s)
     }
 }
 
 impl Password<'_> {
     pub fn is_valid(&self, ruleset: Ruleset) -> bool {
-        self.requirements.met_by_password(&self.password, ruleset)
+        self.requirements.met_by_password(self.password, ruleset)
+    }
+}
+
+impl fmt::Display for Password<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Requirements { num1, num2, letter } = self.requirements;
+        write!(f, "{}-{} {}: {}", num1, num2, letter, self.password)
     }
 }
 
@@ -35,23 +51,6 @@ struct Requirements {
 }
 
 impl Requirements {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, num1) = extract_digits(s)?;
-        let s = tag("-", s)?;
-        let (s, num2) = extract_digits(s)?;
-        let s = tag(" ", s)?;
-        let (s, letter) = extract_char(s)?;
-
-        Ok((
-            s,
-            Self {
-                num1: num1.parse().unwrap(),
-                num2: num2.parse().unwrap(),
-                letter,
-            },
-        ))
-    }
-
     fn met_by_password(&self, password: &str, ruleset: Ruleset) -> bool {
         match ruleset {
             Ruleset::Old => {
@@ -61,13 +60,16 @@ impl Requirements {
                 (self.num1..=self.num2).contains(&num_occurrences_of_letter)
             }
             Ruleset::New => {
-                let is_first_position_correct =
-                    password.chars().nth(self.num1 - 1).unwrap() == self.letter;
-
-                let is_second_position_correct =
-                    password.chars().nth(self.num2 - 1).unwrap() == self.letter;
-
-                is_first_position_correct != is_second_position_correct
+                // Positions are one-based; one that lies outside the password
+                // cannot hold the letter.
+                let is_position_correct = |position: usize| {
+                    position
+                        .checked_sub(1)
+                        .and_then(|idx| password.chars().nth(idx))
+                        == Some(self.letter)
+                };
+
+                is_position_correct(self.num1) != is_position_correct(self.num2)
             }
         }
     }
@@ -96,6 +98,12 @@ mod parsing_tests {
             }),
         );
     }
+
+    #[test]
+    fn display_round_trips() {
+        let password = "2-9 c: ccccccccc";
+        assert_eq!(Password::try_from(password).unwrap().to_string(), password);
+    }
 }
 
 #[cfg(test)]
@@ -113,8 +121,8 @@ mod validation_tests {
             password: "abcde",
         };
 
-        assert_eq!(password.is_valid(Ruleset::Old), true);
-        assert_eq!(password.is_valid(Ruleset::New), true);
+        assert!(password.is_valid(Ruleset::Old));
+        assert!(password.is_valid(Ruleset::New));
     }
 
     #[test]
@@ -128,8 +136,22 @@ mod validation_tests {
             password: "cdefg",
         };
 
-        assert_eq!(password.is_valid(Ruleset::Old), false);
-        assert_eq!(password.is_valid(Ruleset::New), false);
+        assert!(!password.is_valid(Ruleset::Old));
+        assert!(!password.is_valid(Ruleset::New));
+    }
+
+    #[test]
+    fn positions_outside_password() {
+        let password = Password {
+            requirements: Requirements {
+                num1: 0,
+                num2: 4,
+                letter: 'a',
+            },
+            password: "abc",
+        };
+
+        assert!(!password.is_valid(Ruleset::New));
     }
 
     #[test]
@@ -143,7 +165,7 @@ mod validation_tests {
             password: "ccccccccc",
         };
 
-        assert_eq!(password.is_valid(Ruleset::Old), true);
-        assert_eq!(password.is_valid(Ruleset::New), false);
+        assert!(password.is_valid(Ruleset::Old));
+        assert!(!password.is_valid(Ruleset::New));
     }
 }
@@ -1,6 +1,11 @@
-use crate::parsing::{extract_digits, tag};
+use crate::parsing::{all_consuming, in_range, template, unsigned, Field, ParseError};
+use std::fmt;
 use std::str::FromStr;
 
+// Large enough for any real present, and small enough that none of the areas,
+// lengths and volumes calculated from a present’s dimensions can overflow.
+const MAX_DIMENSION: u32 = 1000;
+
 #[derive(Debug, PartialEq)]
 pub struct Present {
     l: u32,
@@ -41,25 +46,33 @@ impl Present {
     }
 }
 
-impl FromStr for Present {
-    type Err = String;
+impl Present {
+    fn new(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, (Dimension(l), Dimension(w), Dimension(h))) = template("{l}x{w}x{h}", s)?;
+        Ok((s, Self { l, w, h }))
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (s, l) = extract_digits(s)?;
-        let s = tag("x", s)?;
-        let (s, w) = extract_digits(s)?;
-        let s = tag("x", s)?;
-        let (s, h) = extract_digits(s)?;
+struct Dimension(u32);
 
-        if !s.is_empty() {
-            return Err("parser did not consume entire input".to_string());
-        }
+impl Field<'_> for Dimension {
+    fn parse_field(s: &str) -> Result<(&str, Self), ParseError> {
+        let (s, dimension) = in_range(unsigned, ..=MAX_DIMENSION, s)?;
+        Ok((s, Self(dimension)))
+    }
+}
 
-        let l = l.parse().unwrap();
-        let w = w.parse().unwrap();
-        let h = h.parse().unwrap();
+impl FromStr for Present {
+    type Err = ParseError;
 
-        Ok(Self { l, w, h })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::new, s)
+    }
+}
+
+impl fmt::Display for Present {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}x{}", self.l, self.w, self.h)
     }
 }
 
@@ -78,6 +91,16 @@ mod parsing_tests {
             }),
         );
     }
+
+    #[test]
+    fn reject_huge_present() {
+        assert!("10x20x3000".parse::<Present>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        assert_eq!("2x3x4".parse::<Present>().unwrap().to_string(), "2x3x4");
+    }
 }
 
 #[cfg(test)]
@@ -1,48 +1,97 @@
+use crate::parsing::{
+    all_consuming, collect_all, parse_lines, read_lines, unsigned, ParseError, ReadError,
+};
+use std::io::BufRead;
+use std::str::FromStr;
+
 pub struct Input {
     data: Vec<u64>,
 }
 
 impl Input {
-    pub fn new(s: &str) -> Self {
-        Self {
-            data: s.lines().map(|line| line.parse().unwrap()).collect(),
-        }
+    pub fn read(reader: impl BufRead) -> Result<Self, Vec<ReadError>> {
+        let data = collect_all(read_lines(reader, |line| all_consuming(unsigned, line)))?;
+        Ok(Self { data })
     }
 
-    pub fn determine_weakness(&self) -> u64 {
-        let first_invalid_number = self.first_invalid_number();
+    pub fn determine_weakness(&self) -> Option<u64> {
+        let first_invalid_number = self.first_invalid_number()?;
 
         for start_idx in 0..self.data.len() {
             for end_idx in start_idx + 2..self.data.len() {
                 let range = &self.data[start_idx..end_idx];
+                let sum = range.iter().try_fold(0_u64, |sum, n| sum.checked_add(*n));
 
-                if range.iter().sum::<u64>() == first_invalid_number {
-                    let smallest_in_range = range.iter().min().unwrap();
-                    let largest_in_range = range.iter().max().unwrap();
+                if sum == Some(first_invalid_number) {
+                    let smallest_in_range = range.iter().min()?;
+                    let largest_in_range = range.iter().max()?;
 
-                    return smallest_in_range + largest_in_range;
+                    return smallest_in_range.checked_add(*largest_in_range);
                 }
             }
         }
 
-        unreachable!()
+        None
     }
 
-    pub fn first_invalid_number(&self) -> u64 {
+    pub fn first_invalid_number(&self) -> Option<u64> {
         'data: for (idx, current) in self.data.iter().copied().enumerate().skip(25) {
             let last_25_numbers = &self.data[idx - 25..idx];
 
             for x in last_25_numbers {
                 for y in last_25_numbers {
-                    if x + y == current {
+                    if x.checked_add(*y) == Some(current) {
                         continue 'data;
                     }
                 }
             }
 
-            return current;
+            return Some(current);
         }
 
-        unreachable!()
+        None
+    }
+}
+
+impl FromStr for Input {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data =
+            parse_lines(s, |line| all_consuming(unsigned, line)).collect::<Result<_, _>>()?;
+        Ok(Self { data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> Input {
+        let mut numbers: Vec<_> = (1..=25).map(|n| n.to_string()).collect();
+        numbers.extend(["26", "49", "100", "50"].iter().map(|n| n.to_string()));
+
+        numbers.join("\n").parse().unwrap()
+    }
+
+    #[test]
+    fn first_invalid_number() {
+        assert_eq!(input().first_invalid_number(), Some(100));
+    }
+
+    #[test]
+    fn no_invalid_number_in_preamble() {
+        let input: Input = "1\n2\n3".parse().unwrap();
+        assert_eq!(input.first_invalid_number(), None);
+        assert_eq!(input.determine_weakness(), None);
+    }
+
+    #[test]
+    fn sums_that_overflow_do_not_match() {
+        let mut numbers = vec![u64::MAX.to_string(); 25];
+        numbers.push("1".to_string());
+
+        let input: Input = numbers.join("\n").parse().unwrap();
+        assert_eq!(input.first_invalid_number(), Some(1));
     }
 }
@@ -0,0 +1,229 @@
+//! Feeds random and mangled input to every parser in the crate, checking that
+//! none of them panic, and that neither do the puzzle solutions run on
+//! whatever they accept.
+
+use aoc::apartment::Directions;
+use aoc::boarding::BoardingPass;
+use aoc::charging::Adapters;
+use aoc::customs::Group;
+use aoc::geology::{Map, Slope};
+use aoc::houses::Moves;
+use aoc::light_grid::{Grid, ScalarLight};
+use aoc::luggage::{Bag, Rules};
+use aoc::naughty_or_nice_strings::{Judgement, Ruleset};
+use aoc::passport::Passport;
+use aoc::password::{self, Password};
+use aoc::presents::Present;
+use aoc::xmas;
+use aoc::{circuit, console, light_grid};
+use std::convert::TryFrom;
+
+const ITERATIONS: usize = 500;
+
+// Characters that appear in the puzzle inputs, so that mangled input has a
+// chance of getting past the first few parsers.
+const ALPHABET: &[char] = &[
+    '0', '1', '9', '+', '-', 'x', ',', ':', '#', '.', ' ', '\n', '\r', '\t', 'F', 'B', 'L', 'R',
+    'a', 'z', 'A', 'N', 'D', 'O', '>', '(', ')', '^', 'v', 'é',
+];
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        // xorshift64
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn char(&mut self) -> char {
+        ALPHABET[self.below(ALPHABET.len())]
+    }
+}
+
+fn random_input(rng: &mut Rng) -> String {
+    let bytes: Vec<_> = (0..rng.below(64)).map(|_| rng.next() as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn mangled_input(rng: &mut Rng, sample: &str) -> String {
+    let lines: Vec<_> = sample.lines().collect();
+    let start = rng.below(lines.len());
+    let end = (start + 1 + rng.below(40)).min(lines.len());
+
+    let mut chars: Vec<_> = lines[start..end].join("\n").chars().collect();
+
+    for _ in 0..=rng.below(4) {
+        let idx = rng.below(chars.len() + 1);
+
+        match rng.below(3) {
+            0 if idx < chars.len() => {
+                chars.remove(idx);
+            }
+            1 if idx < chars.len() => chars[idx] = rng.char(),
+            _ => chars.insert(idx, rng.char()),
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+fn inputs(sample: &str) -> impl Iterator<Item = String> + '_ {
+    let mut rng = Rng(sample.len() as u64 | 1);
+
+    (0..ITERATIONS).map(move |i| {
+        if i % 4 == 0 {
+            random_input(&mut rng)
+        } else {
+            mangled_input(&mut rng, sample)
+        }
+    })
+}
+
+#[test]
+fn apartment() {
+    for input in inputs(include_str!("../src/bin/apartment_input")) {
+        Directions::new(&input).final_floor();
+        Directions::new(&input).first_basement_pos();
+    }
+}
+
+#[test]
+fn boarding() {
+    for input in inputs(include_str!("../src/bin/boarding_input")) {
+        if let Ok(boarding_pass) = input.parse::<BoardingPass>() {
+            boarding_pass.location();
+        }
+    }
+}
+
+#[test]
+fn charging() {
+    for input in inputs(include_str!("../src/bin/charging_input")) {
+        if let Ok(mut adapters) = input.parse::<Adapters>() {
+            adapters.differences_to_outlet();
+        }
+    }
+}
+
+#[test]
+fn circuit() {
+    for input in inputs(include_str!("../src/bin/circuit_input")) {
+        let _ = input.parse::<circuit::Instruction>();
+    }
+}
+
+#[test]
+fn console() {
+    for input in inputs(include_str!("../src/bin/console_input")) {
+        let _ = input.parse::<console::Instruction>();
+    }
+}
+
+#[test]
+fn customs() {
+    for input in inputs(include_str!("../src/bin/customs_input")) {
+        let group = Group::from(input.as_str());
+        group.num_questions();
+        group.num_questions_all_have();
+    }
+}
+
+#[test]
+fn geology() {
+    for input in inputs(include_str!("../src/bin/geology_input")) {
+        if let Ok(map) = input.parse::<Map>() {
+            map.num_trees_in_path(Slope { right: 3, down: 1 });
+        }
+    }
+}
+
+#[test]
+fn houses() {
+    for input in inputs(include_str!("../src/bin/houses_input")) {
+        if let Some(moves) = Moves::new(&input) {
+            moves.num_houses_with_presents(false);
+            moves.num_houses_with_presents(true);
+        }
+    }
+}
+
+#[test]
+fn light_grid() {
+    let mut grid: Grid<ScalarLight> = Grid::default();
+
+    for input in inputs(include_str!("../src/bin/light_grid_input")) {
+        if let Ok(instruction) = input.parse::<light_grid::Instruction>() {
+            grid.apply(instruction);
+        }
+    }
+}
+
+#[test]
+fn luggage() {
+    for input in inputs(include_str!("../src/bin/luggage_input")) {
+        if let Ok(rules) = input.parse::<Rules>() {
+            let bag = Bag {
+                modifier: "shiny".to_string(),
+                color: "gold".to_string(),
+            };
+
+            rules.num_bags_that_can_contain(&bag);
+            let _ = rules.num_bags_contained_by(&bag);
+        }
+    }
+}
+
+#[test]
+fn naughty_or_nice_strings() {
+    for input in inputs(include_str!("../src/bin/naughty_or_nice_strings_input")) {
+        Judgement::of(&input, Ruleset::Old);
+        Judgement::of(&input, Ruleset::New);
+    }
+}
+
+#[test]
+fn passport() {
+    for input in inputs(include_str!("../src/bin/passport_input")) {
+        if let Ok(passport) = Passport::try_from(input.as_str()) {
+            passport.is_complete();
+            passport.is_valid();
+        }
+    }
+}
+
+#[test]
+fn password() {
+    for input in inputs(include_str!("../src/bin/password_input")) {
+        if let Ok(password) = Password::try_from(input.as_str()) {
+            password.is_valid(password::Ruleset::Old);
+            password.is_valid(password::Ruleset::New);
+        }
+    }
+}
+
+#[test]
+fn presents() {
+    for input in inputs(include_str!("../src/bin/presents_input")) {
+        if let Ok(present) = input.parse::<Present>() {
+            present.wrapping_paper_area();
+            present.ribbon_length();
+        }
+    }
+}
+
+#[test]
+fn xmas() {
+    for input in inputs(include_str!("../src/bin/xmas_input")) {
+        if let Ok(input) = input.parse::<xmas::Input>() {
+            input.first_invalid_number();
+            input.determine_weakness();
+        }
+    }
+}
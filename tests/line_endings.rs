@@ -0,0 +1,192 @@
+//! Checks that every puzzle gives the same answers when its input has CRLF line
+//! endings, trailing whitespace or extra blank lines at the end, as it can on
+//! a Windows checkout.
+
+use aoc::apartment::Directions;
+use aoc::boarding::BoardingPass;
+use aoc::charging::Adapters;
+use aoc::customs::Group;
+use aoc::expense_report;
+use aoc::geology::{Map, Slope};
+use aoc::houses::Moves;
+use aoc::light_grid::{BinaryLight, Grid, Instruction};
+use aoc::luggage::{Bag, Rules};
+use aoc::naughty_or_nice_strings::{Judgement, Ruleset};
+use aoc::parsing::{all_consuming, groups, lines, parse_groups, parse_lines, unsigned};
+use aoc::passport::Passport;
+use aoc::password::{self, Password};
+use aoc::presents::Present;
+use aoc::xmas::Input;
+use aoc::{circuit, console};
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+fn assert_same_answers<T: Debug + PartialEq>(input: &str, answers: impl Fn(&str) -> T) {
+    let expected = answers(input);
+
+    let crlf = input.replace('\n', "\r\n");
+    assert_eq!(answers(&crlf), expected);
+
+    let messy = input.replace('\n', " \t\r\n") + "\r\n\r\n";
+    assert_eq!(answers(&messy), expected);
+}
+
+#[test]
+fn apartment() {
+    assert_same_answers(include_str!("../src/bin/apartment_input"), |input| {
+        let directions = Directions::new(input);
+        (
+            directions.clone().final_floor(),
+            directions.first_basement_pos(),
+        )
+    });
+}
+
+#[test]
+fn boarding() {
+    assert_same_answers(include_str!("../src/bin/boarding_input"), |input| {
+        parse_lines(input, |line| line.parse::<BoardingPass>())
+            .map(|pass| pass.unwrap().location().id())
+            .max()
+    });
+}
+
+#[test]
+fn charging() {
+    assert_same_answers(include_str!("../src/bin/charging_input"), |input| {
+        let mut adapters: Adapters = input.parse().unwrap();
+        adapters.differences_to_outlet()
+    });
+}
+
+#[test]
+fn circuit() {
+    assert_same_answers(include_str!("../src/bin/circuit_input"), |input| {
+        let instructions = parse_lines(input, str::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        circuit::Circuit { instructions }.emulate().unwrap()["a"]
+    });
+}
+
+#[test]
+fn console() {
+    assert_same_answers(include_str!("../src/bin/console_input"), |input| {
+        parse_lines(input, str::parse::<console::Instruction>)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    });
+}
+
+#[test]
+fn customs() {
+    assert_same_answers(include_str!("../src/bin/customs_input"), |input| {
+        groups(input)
+            .map(|group| {
+                let group = Group::from(group.as_str());
+                (group.num_questions(), group.num_questions_all_have())
+            })
+            .collect::<Vec<_>>()
+    });
+}
+
+#[test]
+fn expense_report() {
+    assert_same_answers(include_str!("../src/bin/expense_report_input"), |input| {
+        let expenses: Vec<_> = parse_lines(input, |line| all_consuming(unsigned, line))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        expense_report::two_add_to_2020(&expenses)
+    });
+}
+
+#[test]
+fn geology() {
+    assert_same_answers(include_str!("../src/bin/geology_input"), |input| {
+        let map: Map = input.parse().unwrap();
+        map.num_trees_in_path(Slope { right: 3, down: 1 })
+    });
+}
+
+#[test]
+fn houses() {
+    assert_same_answers(include_str!("../src/bin/houses_input"), |input| {
+        Moves::new(input).map(|moves| moves.num_houses_with_presents(true))
+    });
+}
+
+#[test]
+fn light_grid() {
+    assert_same_answers(include_str!("../src/bin/light_grid_input"), |input| {
+        let mut grid: Grid<BinaryLight> = Grid::default();
+
+        for instruction in parse_lines(input, str::parse::<Instruction>) {
+            grid.apply(instruction.unwrap());
+        }
+
+        grid.total_brightness()
+    });
+}
+
+#[test]
+fn luggage() {
+    assert_same_answers(include_str!("../src/bin/luggage_input"), |input| {
+        let rules: Rules = input.parse().unwrap();
+
+        rules
+            .num_bags_contained_by(&Bag {
+                modifier: "shiny".to_string(),
+                color: "gold".to_string(),
+            })
+            .unwrap()
+    });
+}
+
+#[test]
+fn naughty_or_nice_strings() {
+    assert_same_answers(
+        include_str!("../src/bin/naughty_or_nice_strings_input"),
+        |input| {
+            lines(input)
+                .filter(|line| Judgement::of(line, Ruleset::New) == Judgement::Nice)
+                .count()
+        },
+    );
+}
+
+#[test]
+fn passport() {
+    assert_same_answers(include_str!("../src/bin/passport_input"), |input| {
+        parse_groups(input, |group| Passport::try_from(group))
+            .filter(|passport| passport.as_ref().unwrap().is_valid())
+            .count()
+    });
+}
+
+#[test]
+fn password() {
+    assert_same_answers(include_str!("../src/bin/password_input"), |input| {
+        parse_lines(input, Password::try_from)
+            .filter(|password| password.as_ref().unwrap().is_valid(password::Ruleset::New))
+            .count()
+    });
+}
+
+#[test]
+fn presents() {
+    assert_same_answers(include_str!("../src/bin/presents_input"), |input| {
+        parse_lines(input, str::parse::<Present>)
+            .map(|present| present.unwrap().ribbon_length())
+            .sum::<u32>()
+    });
+}
+
+#[test]
+fn xmas() {
+    assert_same_answers(include_str!("../src/bin/xmas_input"), |input| {
+        let input: Input = input.parse().unwrap();
+        input.determine_weakness()
+    });
+}
@@ -0,0 +1,57 @@
+//! Checks that writing out each puzzle input after parsing it gives back
+//! something that parses to the same thing.
+
+use aoc::luggage::Rules;
+use aoc::password::Password;
+use aoc::presents::Present;
+use aoc::{circuit, console, light_grid};
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+fn assert_lines_round_trip<T>(input: &str)
+where
+    T: FromStr + Display + PartialEq + Debug,
+    T::Err: Debug,
+{
+    for line in input.lines() {
+        let parsed = line.parse::<T>().unwrap();
+        assert_eq!(parsed.to_string().parse::<T>().unwrap(), parsed);
+    }
+}
+
+#[test]
+fn circuit() {
+    assert_lines_round_trip::<circuit::Instruction>(include_str!("../src/bin/circuit_input"));
+}
+
+#[test]
+fn console() {
+    assert_lines_round_trip::<console::Instruction>(include_str!("../src/bin/console_input"));
+}
+
+#[test]
+fn light_grid() {
+    assert_lines_round_trip::<light_grid::Instruction>(include_str!("../src/bin/light_grid_input"));
+}
+
+#[test]
+fn luggage() {
+    let rules: Rules = include_str!("../src/bin/luggage_input").parse().unwrap();
+    assert_eq!(rules.to_string().parse::<Rules>().unwrap(), rules);
+}
+
+#[test]
+fn password() {
+    for line in include_str!("../src/bin/password_input").lines() {
+        let password = Password::try_from(line).unwrap();
+        let displayed = password.to_string();
+
+        assert_eq!(Password::try_from(displayed.as_str()).unwrap(), password);
+    }
+}
+
+#[test]
+fn presents() {
+    assert_lines_round_trip::<Present>(include_str!("../src/bin/presents_input"));
+}
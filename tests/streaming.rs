@@ -0,0 +1,104 @@
+//! Checks that reading each streamable puzzle input from a `BufRead` gives the
+//! same answers as parsing it from a string.
+
+use aoc::console::{self, Vm};
+use aoc::customs::Group;
+use aoc::light_grid::{self, BinaryLight, Grid};
+use aoc::parsing::{groups, parse_groups, parse_lines};
+use aoc::passport::Passport;
+use aoc::xmas::Input;
+use std::convert::TryFrom;
+
+#[test]
+fn console() {
+    const INPUT: &str = include_str!("../src/bin/console_input");
+
+    let instructions: Vec<console::Instruction> = parse_lines(INPUT, str::parse)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let mut from_str = Vm::new(instructions.into_iter());
+    let mut read = Vm::read(INPUT.as_bytes()).unwrap();
+
+    assert_eq!(
+        read.accumulator_before_loop(),
+        from_str.accumulator_before_loop(),
+    );
+}
+
+#[test]
+fn customs() {
+    const INPUT: &str = include_str!("../src/bin/customs_input");
+
+    let from_str: Vec<_> = groups(INPUT)
+        .map(|group| Group::from(group.as_str()).num_questions_all_have())
+        .collect();
+
+    let read: Vec<_> = Group::read_all(INPUT.as_bytes())
+        .map(|group| group.unwrap().num_questions_all_have())
+        .collect();
+
+    assert_eq!(read, from_str);
+}
+
+#[test]
+fn light_grid() {
+    const INPUT: &str = include_str!("../src/bin/light_grid_input");
+
+    let from_str: Vec<light_grid::Instruction> = parse_lines(INPUT, str::parse)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let read: Vec<_> = light_grid::Instruction::read_all(INPUT.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(read, from_str);
+
+    let mut grid: Grid<BinaryLight> = Grid::default();
+
+    for instruction in light_grid::Instruction::read_all(INPUT.as_bytes()) {
+        grid.apply(instruction.unwrap());
+    }
+
+    assert_eq!(grid.total_brightness(), 543903);
+}
+
+#[test]
+fn passport() {
+    const INPUT: &str = include_str!("../src/bin/passport_input");
+
+    let from_str: Vec<_> = parse_groups(INPUT, |group| Passport::try_from(group))
+        .map(|passport| passport.unwrap().is_valid())
+        .collect();
+
+    let read: Vec<_> = Passport::read_all(INPUT.as_bytes())
+        .map(|passport| passport.unwrap().is_valid())
+        .collect();
+
+    assert_eq!(read, from_str);
+}
+
+#[test]
+fn xmas() {
+    const INPUT: &str = include_str!("../src/bin/xmas_input");
+
+    let from_str: Input = INPUT.parse().unwrap();
+    let read = Input::read(INPUT.as_bytes()).unwrap();
+
+    assert_eq!(read.first_invalid_number(), from_str.first_invalid_number());
+}
+
+#[test]
+fn report_every_error_with_its_position() {
+    let errors = Vm::read("nop +0\nacc x\njmp\n".as_bytes()).unwrap_err();
+
+    let lines: Vec<_> = errors
+        .iter()
+        .map(|error| error.render("console_input"))
+        .collect();
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("console_input:2:5"));
+    assert!(lines[1].contains("console_input:3:4"));
+}